@@ -1,4 +1,12 @@
+pub mod analysis;
+pub mod error;
 pub mod mono;
+pub mod process;
+pub mod resample;
+#[cfg(feature = "simd")]
+pub mod simd;
+#[cfg(feature = "fft")]
+pub mod spectrum;
 pub mod stereo;
 pub mod utils;
 
@@ -85,8 +93,14 @@ pub trait Buffer {
 }
 
 pub mod prelude {
+    pub use super::analysis::{BufferStats, ContentClass, StreamingStats};
+    pub use super::error::BufferError;
     pub use super::mono::MonoBuffer;
-    pub use super::stereo::StereoBuffer;
+    pub use super::process::{Operation, ProcessChain};
+    pub use super::resample::{ResampleQuality, Resampler};
+    #[cfg(feature = "fft")]
+    pub use super::spectrum::{Key, Mode};
+    pub use super::stereo::{BufferSnapshot, Channel, ChannelMap, MonoMix, StereoBuffer};
     pub use super::utils::*;
     pub use super::Buffer;
     pub use super::BufferLayout;
@@ -0,0 +1,124 @@
+/// Coarse classification of audio content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentClass {
+    Speech,
+    Music,
+    Unknown,
+}
+
+/// Length of the analysis frames in seconds
+const FRAME_SECONDS: f32 = 0.02;
+/// Content shorter than this many analysis frames is not classified
+const MIN_FRAMES: usize = 50;
+
+/// Heuristically classify content as speech or music.
+///
+/// Two classic time domain features are computed over 20ms frames:
+/// - the low short-time energy ratio, the share of frames quieter than half the mean energy.
+///   Speech alternates syllables and pauses, so it is high, while music is mostly sustained.
+/// - the high zero-crossing rate ratio, the share of frames crossing zero 1.5 times more often than
+///   on average. Speech alternates voiced and unvoiced sounds, so it is high as well.
+///
+/// This is not a trained model, it is only meant to pick sensible processing defaults.
+/// Silent or very short content (under a second) is `Unknown`.
+pub fn classify_content(samples: &[f32], sample_rate: u32) -> ContentClass {
+    let frame_len = std::cmp::max((sample_rate as f32 * FRAME_SECONDS) as usize, 1);
+    let frames: Vec<(f32, f32)> = samples
+        .chunks_exact(frame_len)
+        .map(|frame| {
+            let energy = frame.iter().map(|x| x * x).sum::<f32>() / frame_len as f32;
+            let crossings = frame
+                .windows(2)
+                .filter(|pair| (pair[0] >= 0.0) != (pair[1] >= 0.0))
+                .count();
+            (energy, crossings as f32 / frame_len as f32)
+        })
+        .collect();
+
+    if frames.len() < MIN_FRAMES {
+        return ContentClass::Unknown;
+    }
+
+    let count = frames.len() as f32;
+    let mean_energy = frames.iter().map(|(energy, _)| energy).sum::<f32>() / count;
+    let mean_zcr = frames.iter().map(|(_, zcr)| zcr).sum::<f32>() / count;
+
+    if mean_energy <= f32::EPSILON {
+        return ContentClass::Unknown;
+    }
+
+    let lster = frames
+        .iter()
+        .filter(|(energy, _)| *energy < 0.5 * mean_energy)
+        .count() as f32
+        / count;
+    let hzcrr = frames
+        .iter()
+        .filter(|(_, zcr)| *zcr > 1.5 * mean_zcr)
+        .count() as f32
+        / count;
+
+    if lster > 0.3 && hzcrr > 0.1 {
+        ContentClass::Speech
+    } else if lster < 0.15 {
+        ContentClass::Music
+    } else {
+        ContentClass::Unknown
+    }
+}
+
+/// Summary statistics of audio content.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BufferStats {
+    /// Highest absolute sample value
+    pub peak: f32,
+    pub rms: f32,
+    /// Number of samples the statistics were computed over, across all channels
+    pub samples: usize,
+    /// Number of samples beyond full scale
+    pub clipped: usize,
+}
+
+/// Accumulates `BufferStats` over content which is never entirely in memory, such as streamed pages.
+#[derive(Debug, Clone, Default)]
+pub struct StreamingStats {
+    peak: f32,
+    sum_of_squares: f64,
+    samples: usize,
+    clipped: usize,
+}
+
+impl StreamingStats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Account for the next samples of the content.
+    pub fn push(&mut self, slice: &[f32]) {
+        for sample in slice {
+            let magnitude = sample.abs();
+            self.peak = self.peak.max(magnitude);
+            self.sum_of_squares += (*sample as f64) * (*sample as f64);
+            if magnitude > 1.0 {
+                self.clipped += 1;
+            }
+        }
+        self.samples += slice.len();
+    }
+
+    /// Statistics of all the samples pushed so far.
+    pub fn finish(&self) -> BufferStats {
+        let rms = if self.samples == 0 {
+            0.0
+        } else {
+            (self.sum_of_squares / self.samples as f64).sqrt() as f32
+        };
+
+        BufferStats {
+            peak: self.peak,
+            rms,
+            samples: self.samples,
+            clipped: self.clipped,
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::{error::Error, fmt};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BufferError {
+    // The requested region runs past the channel capacity
+    OutOfBounds {
+        start: usize,
+        len: usize,
+        capacity: usize,
+    },
+}
+
+impl fmt::Display for BufferError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BufferError::OutOfBounds {
+                start,
+                len,
+                capacity,
+            } => write!(
+                f,
+                "Region of {} samples at {} exceeds the capacity of {}",
+                len, start, capacity
+            ),
+        }
+    }
+}
+
+impl Error for BufferError {}
@@ -1,13 +1,17 @@
-use super::{utils::buffer_with_size, Buffer, BufferLayout};
+use super::{error::BufferError, utils::buffer_with_size, Buffer, BufferLayout};
 
 /// Opinionated buffer for inner operations
 ///
-/// Values exceeding the capacity of samples_written are considered to be uninitialized and non deterministic.
+/// Values past the write cursor hold silence or stale content, and should not be relied upon.
+///
+/// Besides linear appends, the buffer can be used as a bounded ring, see `write_wrapping`.
 pub struct MonoBuffer {
     pub buf: Vec<f32>,
     channel_size: usize,
     /// The location from which copy and swap occurs in this buffer
-    samples_written: usize,
+    write_cursor: usize,
+    /// The location from which ring reads occur, trailing the write cursor
+    read_cursor: usize,
 }
 
 impl<Idx> std::ops::Index<Idx> for MonoBuffer
@@ -35,7 +39,8 @@ impl MonoBuffer {
         Self {
             buf: buffer_with_size(capacity),
             channel_size: capacity,
-            samples_written: 0,
+            write_cursor: 0,
+            read_cursor: 0,
         }
     }
 
@@ -44,7 +49,8 @@ impl MonoBuffer {
         Self {
             buf: vec,
             channel_size,
-            samples_written: 0,
+            write_cursor: 0,
+            read_cursor: 0,
         }
     }
 
@@ -52,10 +58,13 @@ impl MonoBuffer {
         self.buf.iter()
     }
 
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, f32> {
+        self.buf.iter_mut()
+    }
+
+    /// Extend the buffer over its whole allocation, the new samples being silent.
     fn recalculate_len(&mut self) {
-        unsafe {
-            self.buf.set_len(self.buf.capacity());
-        }
+        self.buf.resize(self.buf.capacity(), 0f32);
         self.channel_size = self.buf.len();
     }
 
@@ -63,6 +72,18 @@ impl MonoBuffer {
         &self.buf[start..start + len]
     }
 
+    /// Same as `slice`, failing instead of panicking when the region exceeds the capacity.
+    pub fn try_slice(&self, start: usize, len: usize) -> Result<&[f32], BufferError> {
+        match start.checked_add(len) {
+            Some(end) if end <= self.buf.len() => Ok(&self.buf[start..end]),
+            _ => Err(BufferError::OutOfBounds {
+                start,
+                len,
+                capacity: self.buf.len(),
+            }),
+        }
+    }
+
     pub fn slice_mut(&mut self, start: usize, len: usize) -> &mut [f32] {
         &mut self.buf[start..start + len]
     }
@@ -75,6 +96,276 @@ impl MonoBuffer {
         &mut self.buf
     }
 
+    /// Copy `len` written samples starting at `start` into a new buffer, with its cursor set to `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the requested region exceeds the written samples.
+    pub fn extract(&self, start: usize, len: usize) -> MonoBuffer {
+        assert!(
+            start + len <= self.write_cursor,
+            "Extracted region exceeds the written samples"
+        );
+
+        Self {
+            buf: self.slice(start, len).to_vec(),
+            channel_size: len,
+            write_cursor: len,
+            read_cursor: 0,
+        }
+    }
+
+    /// Insert `frames` samples of silence before the written samples, shifting them to the right.
+    ///
+    /// Reserves exactly the missing space if needed.
+    pub fn prepend_silence(&mut self, frames: usize) {
+        let overflow = self.overflow_on(frames);
+        if overflow > 0 {
+            self.reserve_exact(overflow);
+        }
+
+        self.buf.copy_within(0..self.write_cursor, frames);
+        self.buf[..frames].fill(0f32);
+        self.write_cursor += frames;
+    }
+
+    /// Truncate the written samples to `frames`, or pad them with silence up to `frames`.
+    ///
+    /// Reserves exactly the missing space if needed.
+    pub fn resize(&mut self, frames: usize) {
+        let overflow = frames.saturating_sub(self.channel_capacity());
+        if overflow > 0 {
+            self.reserve_exact(overflow);
+        }
+
+        if frames > self.write_cursor {
+            self.buf[self.write_cursor..frames].fill(0f32);
+        }
+        self.write_cursor = frames;
+    }
+
+    /// Repeat the written samples until `target_frames` samples are written, truncating the last repeat.
+    ///
+    /// Each repeat is blended into the tail of the previous one over `crossfade` samples to avoid clicks.
+    /// The crossfade is shortened if needed, so that every repeat adds at least one sample.
+    /// Written content longer than `target_frames` is truncated, an empty buffer is padded with silence.
+    ///
+    /// Reserves exactly the missing space if needed.
+    pub fn loop_extend(&mut self, target_frames: usize, crossfade: usize) {
+        let overflow = target_frames.saturating_sub(self.channel_capacity());
+        if overflow > 0 {
+            self.reserve_exact(overflow);
+        }
+
+        let len = self.write_cursor;
+        if len == 0 {
+            self.buf[..target_frames].fill(0f32);
+            self.write_cursor = target_frames;
+            return;
+        }
+
+        let source = self.buf[..len].to_vec();
+        let crossfade = std::cmp::min(crossfade, len - 1);
+
+        while self.write_cursor < target_frames {
+            let start = self.write_cursor - crossfade;
+            for (i, sample) in source[..crossfade].iter().enumerate() {
+                let gain = (i + 1) as f32 / (crossfade + 1) as f32;
+                self.buf[start + i] = self.buf[start + i] * (1.0 - gain) + sample * gain;
+            }
+
+            let end = std::cmp::min(self.write_cursor + len - crossfade, target_frames);
+            let copied = end - self.write_cursor;
+            self.buf[self.write_cursor..end]
+                .copy_from_slice(&source[crossfade..crossfade + copied]);
+            self.write_cursor = end;
+        }
+
+        self.write_cursor = target_frames;
+    }
+
+    /// Blend the last `frames` written samples into the first ones and drop them, so that looping
+    /// over the written samples wraps around without a click.
+    ///
+    /// The crossfade is shortened if needed, so that at least one sample is left.
+    pub fn crossfade_loop(&mut self, frames: usize) {
+        let len = self.write_cursor;
+        if len == 0 {
+            return;
+        }
+
+        let frames = std::cmp::min(frames, len - 1);
+        let end = len - frames;
+        for i in 0..frames {
+            let gain = (i + 1) as f32 / (frames + 1) as f32;
+            self.buf[i] = self.buf[end + i] * (1.0 - gain) + self.buf[i] * gain;
+        }
+        self.write_cursor = end;
+    }
+
+    /// Apply a pre-emphasis filter `y[n] = x[n] - coeff * x[n - 1]` to the written samples, in place.
+    ///
+    /// Boosts high frequencies, the usual front-end of speech analysis. The sample preceding the
+    /// written region is taken to be silence.
+    pub fn pre_emphasis(&mut self, coeff: f32) {
+        let mut previous = 0f32;
+        for sample in self.buf[..self.write_cursor].iter_mut() {
+            let input = *sample;
+            *sample = input - coeff * previous;
+            previous = input;
+        }
+    }
+
+    /// Apply the inverse of `pre_emphasis`, `y[n] = x[n] + coeff * y[n - 1]`, in place.
+    pub fn de_emphasis(&mut self, coeff: f32) {
+        let mut previous = 0f32;
+        for sample in self.buf[..self.write_cursor].iter_mut() {
+            *sample += coeff * previous;
+            previous = *sample;
+        }
+    }
+
+    /// Multiply the written samples by `gain`, without clamping them.
+    ///
+    /// Returns whether any sample ended up outside `[-1, 1]`, which would clip on playback.
+    pub fn apply_gain(&mut self, gain: f32) -> bool {
+        let mut clipped = false;
+        for sample in self.buf[..self.write_cursor].iter_mut() {
+            *sample *= gain;
+            clipped |= sample.abs() > 1.0;
+        }
+        clipped
+    }
+
+    /// Multiply each written sample by the gain at the same position of `envelope`.
+    ///
+    /// Samples past the end of the envelope are left untouched.
+    pub fn apply_gain_envelope(&mut self, envelope: &[f32]) {
+        self.buf[..self.write_cursor]
+            .iter_mut()
+            .zip(envelope)
+            .for_each(|(sample, gain)| *sample *= gain);
+    }
+
+    /// Linearly fade in the first `frames` written samples, starting from silence.
+    pub fn fade_in(&mut self, frames: usize) {
+        let frames = std::cmp::min(frames, self.write_cursor);
+        for (i, sample) in self.buf[..frames].iter_mut().enumerate() {
+            *sample *= i as f32 / frames as f32;
+        }
+    }
+
+    /// Linearly fade out the last `frames` written samples, ending in silence.
+    pub fn fade_out(&mut self, frames: usize) {
+        let frames = std::cmp::min(frames, self.write_cursor);
+        let start = self.write_cursor - frames;
+        for (i, sample) in self.buf[start..self.write_cursor]
+            .iter_mut()
+            .rev()
+            .enumerate()
+        {
+            *sample *= i as f32 / frames as f32;
+        }
+    }
+
+    /// Limit the written samples to `[-limit, limit]`.
+    pub fn clamp(&mut self, limit: f32) {
+        self.buf[..self.write_cursor]
+            .iter_mut()
+            .for_each(|sample| *sample = sample.clamp(-limit, limit));
+    }
+
+    /// Root mean square of the written samples, 0 when nothing is written.
+    pub fn rms(&self) -> f32 {
+        rms(&self.buf[..self.write_cursor])
+    }
+
+    /// Largest magnitude of the written samples, 0 when nothing is written.
+    pub fn peak(&self) -> f32 {
+        self.buf[..self.write_cursor]
+            .iter()
+            .fold(0f32, |peak, sample| peak.max(sample.abs()))
+    }
+
+    /// Mean of the written samples, the DC offset of the content, 0 when nothing is written.
+    pub fn dc_offset(&self) -> f32 {
+        let written = &self.buf[..self.write_cursor];
+        if written.is_empty() {
+            return 0.0;
+        }
+        let sum: f64 = written.iter().map(|x| *x as f64).sum();
+        (sum / written.len() as f64) as f32
+    }
+
+    /// Subtract the DC offset from the written samples, centering them on 0.
+    pub fn remove_dc(&mut self) {
+        let offset = self.dc_offset();
+        self.buf[..self.write_cursor]
+            .iter_mut()
+            .for_each(|sample| *sample -= offset);
+    }
+
+    /// RMS of consecutive windows of `window_len` written samples, the last one may be shorter.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_len` is 0.
+    pub fn rms_windowed(&self, window_len: usize) -> Vec<f32> {
+        self.buf[..self.write_cursor]
+            .chunks(window_len)
+            .map(rms)
+            .collect()
+    }
+
+    pub fn write_cursor(&self) -> usize {
+        self.write_cursor
+    }
+
+    pub fn read_cursor(&self) -> usize {
+        self.read_cursor
+    }
+
+    /// Samples written but not read yet, wrapping around the end of the buffer.
+    pub fn unread(&self) -> usize {
+        if self.write_cursor >= self.read_cursor {
+            self.write_cursor - self.read_cursor
+        } else {
+            self.channel_size - self.read_cursor + self.write_cursor
+        }
+    }
+
+    /// Samples `write_wrapping` can write before reaching the read cursor.
+    ///
+    /// One sample is always kept free, so that equal cursors mean an empty ring.
+    pub fn ring_space(&self) -> usize {
+        self.channel_size.saturating_sub(self.unread() + 1)
+    }
+
+    /// Write as much of `slice` as fits in the ring, wrapping to the front once the end is reached.
+    ///
+    /// Samples already read at the front are reused, unread ones are never overwritten.
+    /// Returns the number of samples written. The capacity must not change while samples are unread.
+    pub fn write_wrapping(&mut self, slice: &[f32]) -> usize {
+        let len = std::cmp::min(slice.len(), self.ring_space());
+        let tail = std::cmp::min(len, self.channel_size - self.write_cursor);
+        self.buf[self.write_cursor..self.write_cursor + tail].copy_from_slice(&slice[..tail]);
+        self.buf[..len - tail].copy_from_slice(&slice[tail..len]);
+        self.write_cursor = (self.write_cursor + len) % self.channel_size.max(1);
+        len
+    }
+
+    /// Copy up to `output.len()` unread samples into `output`, wrapping like `write_wrapping`.
+    ///
+    /// Returns the number of samples read, the read cursor advances past them.
+    pub fn read_wrapping(&mut self, output: &mut [f32]) -> usize {
+        let len = std::cmp::min(output.len(), self.unread());
+        let tail = std::cmp::min(len, self.channel_size - self.read_cursor);
+        output[..tail].copy_from_slice(&self.buf[self.read_cursor..self.read_cursor + tail]);
+        output[tail..len].copy_from_slice(&self.buf[..len - tail]);
+        self.read_cursor = (self.read_cursor + len) % self.channel_size.max(1);
+        len
+    }
+
     /// Append a slice to all channels, and fill overflow with unappendable content.
     pub fn append_slice_overflow(&mut self, slice: &[f32], overflow: &mut MonoBuffer) {
         let sample_count = slice.len();
@@ -96,12 +387,20 @@ impl MonoBuffer {
     // TODO append audio buffer & audio buffer ref
 }
 
+fn rms(samples: &[f32]) -> f32 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let sum_of_squares: f64 = samples.iter().map(|x| (*x as f64) * (*x as f64)).sum();
+    (sum_of_squares / samples.len() as f64).sqrt() as f32
+}
+
 impl Buffer for MonoBuffer {
     fn append_slice(&mut self, slice: &[f32]) {
         let cursor = self.cursor();
         let end = cursor + slice.len();
         self.buf[cursor..end].copy_from_slice(slice);
-        self.samples_written += slice.len();
+        self.write_cursor += slice.len();
     }
 
     fn layout(&self) -> BufferLayout {
@@ -113,11 +412,12 @@ impl Buffer for MonoBuffer {
     }
 
     fn cursor(&self) -> usize {
-        self.samples_written
+        self.write_cursor
     }
 
     fn clear_cursor(&mut self) {
-        self.samples_written = 0
+        self.write_cursor = 0;
+        self.read_cursor = 0;
     }
 
     fn reserve(&mut self, additional: usize) -> usize {
@@ -137,8 +437,8 @@ impl Buffer for MonoBuffer {
             return;
         }
 
+        self.buf.truncate(self.write_cursor);
         self.buf.shrink_to_fit();
-        self.buf.resize(self.samples_written, 0f32);
         self.channel_size = self.buf.len();
     }
 
@@ -157,8 +457,8 @@ impl Buffer for MonoBuffer {
             return;
         }
 
-        self.buf[self.samples_written..].fill(0f32);
-        self.samples_written = self.channel_size;
+        self.buf[self.write_cursor..].fill(0f32);
+        self.write_cursor = self.channel_size;
     }
 
     fn _0() -> Self {
@@ -0,0 +1,84 @@
+use super::stereo::StereoBuffer;
+
+/// A single in-place processing step of a `ProcessChain`.
+pub enum Operation {
+    /// See `StereoBuffer::apply_gain`
+    Gain(f32),
+    /// See `StereoBuffer::fade_in`
+    FadeIn(usize),
+    /// See `StereoBuffer::fade_out`
+    FadeOut(usize),
+    /// See `StereoBuffer::normalize_peak`
+    Normalize(f32),
+    /// See `StereoBuffer::clamp`
+    Clamp(f32),
+    /// Any other processing
+    Custom(Box<dyn Fn(&mut StereoBuffer) + Send + Sync>),
+}
+
+impl Operation {
+    fn apply(&self, buffer: &mut StereoBuffer) {
+        match self {
+            Operation::Gain(gain) => {
+                buffer.apply_gain(*gain);
+            }
+            Operation::FadeIn(frames) => buffer.fade_in(*frames),
+            Operation::FadeOut(frames) => buffer.fade_out(*frames),
+            Operation::Normalize(target) => buffer.normalize_peak(*target),
+            Operation::Clamp(limit) => buffer.clamp(*limit),
+            Operation::Custom(process) => process(buffer),
+        }
+    }
+}
+
+/// Processing steps applied in the order they were added, such as
+/// `ProcessChain::new().fade_in(64).normalize(0.9).clamp(0.8)`.
+#[derive(Default)]
+pub struct ProcessChain {
+    operations: Vec<Operation>,
+}
+
+impl ProcessChain {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn then(mut self, operation: Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    pub fn gain(self, gain: f32) -> Self {
+        self.then(Operation::Gain(gain))
+    }
+
+    pub fn fade_in(self, frames: usize) -> Self {
+        self.then(Operation::FadeIn(frames))
+    }
+
+    pub fn fade_out(self, frames: usize) -> Self {
+        self.then(Operation::FadeOut(frames))
+    }
+
+    pub fn normalize(self, target: f32) -> Self {
+        self.then(Operation::Normalize(target))
+    }
+
+    pub fn clamp(self, limit: f32) -> Self {
+        self.then(Operation::Clamp(limit))
+    }
+
+    pub fn custom<F>(self, process: F) -> Self
+    where
+        F: Fn(&mut StereoBuffer) + Send + Sync + 'static,
+    {
+        self.then(Operation::Custom(Box::new(process)))
+    }
+
+    /// Apply all the operations to the written region of `buffer`, in order.
+    pub fn apply(&self, buffer: &mut StereoBuffer) {
+        for operation in &self.operations {
+            operation.apply(buffer);
+        }
+    }
+}
@@ -0,0 +1,122 @@
+/// Interpolation used when resampling.
+///
+/// Only linear interpolation is available for now, a windowed sinc path will follow.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ResampleQuality {
+    /// Interpolate linearly between neighbouring frames, cheap but prone to aliasing.
+    #[default]
+    Linear,
+}
+
+/// Stateful linear interpolating resampler for stereo content.
+///
+/// The position and the last frame of each processed chunk are carried over to the next one, so
+/// content processed in consecutive chunks is continuous across chunk boundaries. Positions are
+/// tracked exactly, the same content yields the same frames however it is chunked.
+pub struct Resampler {
+    source_rate: u64,
+    target_rate: u64,
+    /// Output frames issued so far
+    issued: u64,
+    /// Source frames of the previously processed chunks
+    consumed: u64,
+    /// Last frame of the previous chunk
+    last: Option<(f32, f32)>,
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+impl Resampler {
+    pub fn new(source_rate: u32, target_rate: u32) -> Self {
+        Self {
+            source_rate: source_rate as u64,
+            target_rate: target_rate as u64,
+            issued: 0,
+            consumed: 0,
+            last: None,
+        }
+    }
+
+    /// Position of the next output frame relative to the start of the next chunk, in source frames
+    /// scaled by the target rate.
+    ///
+    /// Falls in `[-target_rate, 0)` when it lies between the last frame of the previous chunk and
+    /// the next chunk.
+    fn position(&self) -> i64 {
+        (self.issued * self.source_rate) as i64 - (self.consumed * self.target_rate) as i64
+    }
+
+    /// Resample the next chunk of both channels.
+    ///
+    /// The output length varies from chunk to chunk, since output frames are only issued once
+    /// both of their neighbouring source frames are known.
+    pub fn process(&mut self, left: &[f32], right: &[f32]) -> (Vec<f32>, Vec<f32>) {
+        debug_assert_eq!(left.len(), right.len());
+
+        let len = left.len();
+        if len == 0 {
+            return (Vec::new(), Vec::new());
+        }
+
+        let (last_left, last_right) = self.last.unwrap_or((left[0], right[0]));
+        let at = |channel: &[f32], last: f32, idx: isize| {
+            if idx < 0 {
+                last
+            } else {
+                channel[idx as usize]
+            }
+        };
+
+        let target_rate = self.target_rate as i64;
+        let ahead = std::cmp::max(len as i64 * target_rate - self.position(), 0) as u64;
+        let expected = ahead.div_ceil(self.source_rate) as usize;
+        let mut out_left = Vec::with_capacity(expected);
+        let mut out_right = Vec::with_capacity(expected);
+
+        let mut position = self.position();
+        while position < (len as i64 - 1) * target_rate {
+            let t = position.rem_euclid(target_rate) as f32 / target_rate as f32;
+            let idx = position.div_euclid(target_rate) as isize;
+
+            out_left.push(lerp(
+                at(left, last_left, idx),
+                at(left, last_left, idx + 1),
+                t,
+            ));
+            out_right.push(lerp(
+                at(right, last_right, idx),
+                at(right, last_right, idx + 1),
+                t,
+            ));
+
+            self.issued += 1;
+            position = self.position();
+        }
+
+        self.consumed += len as u64;
+        self.last = Some((left[len - 1], right[len - 1]));
+
+        (out_left, out_right)
+    }
+
+    /// Issue the output frames left past the last processed chunk, once the content ended.
+    ///
+    /// They hold the last frame, there is no following frame to interpolate with.
+    pub fn finish(&mut self) -> (Vec<f32>, Vec<f32>) {
+        let (mut out_left, mut out_right) = (Vec::new(), Vec::new());
+        if let Some((last_left, last_right)) = self.last.take() {
+            while self.position() < 0 {
+                out_left.push(last_left);
+                out_right.push(last_right);
+                self.issued += 1;
+            }
+        }
+        self.issued = 0;
+        self.consumed = 0;
+
+        (out_left, out_right)
+    }
+}
@@ -0,0 +1,124 @@
+/// Scale of a full range i16 sample to f32, a power of two so the multiplication is exact
+const S16_SCALE: f32 = 1.0 / 32_768.0;
+/// Scale of a full range i32 sample to f32, a power of two so the multiplication is exact
+const S32_SCALE: f32 = 1.0 / 2_147_483_648.0;
+
+/// Convert i16 samples to f32, writing `min(input.len(), output.len())` samples.
+///
+/// Matches symphonia's scalar conversion. SSE2 is used on x86_64 and NEON on aarch64, which are
+/// always available there, other targets take the scalar path.
+pub fn s16_to_f32(input: &[i16], output: &mut [f32]) {
+    let len = std::cmp::min(input.len(), output.len());
+    let (input, output) = (&input[..len], &mut output[..len]);
+
+    let done = s16_to_f32_vectorized(input, output);
+    for (sample, converted) in input[done..].iter().zip(&mut output[done..]) {
+        *converted = *sample as f32 * S16_SCALE;
+    }
+}
+
+/// Convert i32 samples to f32, writing `min(input.len(), output.len())` samples.
+///
+/// Same as `s16_to_f32`.
+pub fn s32_to_f32(input: &[i32], output: &mut [f32]) {
+    let len = std::cmp::min(input.len(), output.len());
+    let (input, output) = (&input[..len], &mut output[..len]);
+
+    let done = s32_to_f32_vectorized(input, output);
+    for (sample, converted) in input[done..].iter().zip(&mut output[done..]) {
+        *converted = *sample as f32 * S32_SCALE;
+    }
+}
+
+/// Convert whole lanes of 8 samples, returning the number of samples converted.
+#[cfg(target_arch = "x86_64")]
+fn s16_to_f32_vectorized(input: &[i16], output: &mut [f32]) -> usize {
+    use std::arch::x86_64::*;
+
+    let lanes = input.len() / 8;
+    // SAFETY: SSE2 is part of the x86_64 baseline, and every load and store stays within
+    // the first `lanes * 8` samples of both slices, which have the same length.
+    unsafe {
+        let scale = _mm_set1_ps(S16_SCALE);
+        for lane in 0..lanes {
+            let samples = _mm_loadu_si128(input.as_ptr().add(lane * 8) as *const __m128i);
+            // Sign extend by moving each sample to the high half, then shifting it back down
+            let low = _mm_srai_epi32(_mm_unpacklo_epi16(samples, samples), 16);
+            let high = _mm_srai_epi32(_mm_unpackhi_epi16(samples, samples), 16);
+
+            let out = output.as_mut_ptr().add(lane * 8);
+            _mm_storeu_ps(out, _mm_mul_ps(_mm_cvtepi32_ps(low), scale));
+            _mm_storeu_ps(out.add(4), _mm_mul_ps(_mm_cvtepi32_ps(high), scale));
+        }
+    }
+    lanes * 8
+}
+
+/// Convert whole lanes of 4 samples, returning the number of samples converted.
+#[cfg(target_arch = "x86_64")]
+fn s32_to_f32_vectorized(input: &[i32], output: &mut [f32]) -> usize {
+    use std::arch::x86_64::*;
+
+    let lanes = input.len() / 4;
+    // SAFETY: see `s16_to_f32_vectorized`
+    unsafe {
+        let scale = _mm_set1_ps(S32_SCALE);
+        for lane in 0..lanes {
+            let samples = _mm_loadu_si128(input.as_ptr().add(lane * 4) as *const __m128i);
+            let converted = _mm_mul_ps(_mm_cvtepi32_ps(samples), scale);
+            _mm_storeu_ps(output.as_mut_ptr().add(lane * 4), converted);
+        }
+    }
+    lanes * 4
+}
+
+/// Convert whole lanes of 8 samples, returning the number of samples converted.
+#[cfg(target_arch = "aarch64")]
+fn s16_to_f32_vectorized(input: &[i16], output: &mut [f32]) -> usize {
+    use std::arch::aarch64::*;
+
+    let lanes = input.len() / 8;
+    // SAFETY: NEON is part of the aarch64 baseline, and every load and store stays within
+    // the first `lanes * 8` samples of both slices, which have the same length.
+    unsafe {
+        for lane in 0..lanes {
+            let samples = vld1q_s16(input.as_ptr().add(lane * 8));
+            let low = vcvtq_f32_s32(vmovl_s16(vget_low_s16(samples)));
+            let high = vcvtq_f32_s32(vmovl_high_s16(samples));
+
+            let out = output.as_mut_ptr().add(lane * 8);
+            vst1q_f32(out, vmulq_n_f32(low, S16_SCALE));
+            vst1q_f32(out.add(4), vmulq_n_f32(high, S16_SCALE));
+        }
+    }
+    lanes * 8
+}
+
+/// Convert whole lanes of 4 samples, returning the number of samples converted.
+#[cfg(target_arch = "aarch64")]
+fn s32_to_f32_vectorized(input: &[i32], output: &mut [f32]) -> usize {
+    use std::arch::aarch64::*;
+
+    let lanes = input.len() / 4;
+    // SAFETY: see `s16_to_f32_vectorized`
+    unsafe {
+        for lane in 0..lanes {
+            let samples = vcvtq_f32_s32(vld1q_s32(input.as_ptr().add(lane * 4)));
+            vst1q_f32(
+                output.as_mut_ptr().add(lane * 4),
+                vmulq_n_f32(samples, S32_SCALE),
+            );
+        }
+    }
+    lanes * 4
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn s16_to_f32_vectorized(_input: &[i16], _output: &mut [f32]) -> usize {
+    0
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn s32_to_f32_vectorized(_input: &[i32], _output: &mut [f32]) -> usize {
+    0
+}
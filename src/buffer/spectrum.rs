@@ -0,0 +1,255 @@
+use std::fmt;
+
+use realfft::RealFftPlanner;
+
+use super::{mono::MonoBuffer, stereo::StereoBuffer, Buffer};
+
+/// Krumhansl-Kessler probe tone profile of major keys, starting from the tonic
+const MAJOR_PROFILE: [f32; 12] = [
+    6.35, 2.23, 3.48, 2.33, 4.38, 4.09, 2.52, 5.19, 2.39, 3.66, 2.29, 2.88,
+];
+/// Krumhansl-Kessler probe tone profile of minor keys, starting from the tonic
+const MINOR_PROFILE: [f32; 12] = [
+    6.33, 2.68, 3.52, 5.38, 2.60, 3.53, 2.54, 4.75, 3.98, 2.69, 3.34, 3.17,
+];
+const PITCH_CLASS_NAMES: [&str; 12] = [
+    "C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B",
+];
+
+/// Frequency range accounted for by the chroma, from A1 to roughly B7
+const CHROMA_MIN_HZ: f32 = 55.0;
+const CHROMA_MAX_HZ: f32 = 4000.0;
+const KEY_FFT_SIZE: usize = 8192;
+const KEY_HOP: usize = 4096;
+/// Keys correlating less than this with the chroma are not reported
+const KEY_CONFIDENCE_THRESHOLD: f32 = 0.6;
+
+/// Convert a frequency in Hz to the mel scale.
+pub fn hz_to_mel(hz: f32) -> f32 {
+    2595.0 * (1.0 + hz / 700.0).log10()
+}
+
+/// Convert a mel scale value back to a frequency in Hz.
+pub fn mel_to_hz(mel: f32) -> f32 {
+    700.0 * (10f32.powf(mel / 2595.0) - 1.0)
+}
+
+/// Edges of `n_mels` overlapping bands evenly spaced on the mel scale, from 0Hz to the Nyquist frequency.
+///
+/// Band `i` rises from `edges[i]`, peaks at `edges[i + 1]` and falls back to zero at `edges[i + 2]`.
+pub fn mel_band_edges(n_mels: usize, sample_rate: u32) -> Vec<f32> {
+    let max_mel = hz_to_mel(sample_rate as f32 / 2.0);
+    (0..n_mels + 2)
+        .map(|i| mel_to_hz(max_mel * i as f32 / (n_mels + 1) as f32))
+        .collect()
+}
+
+/// Triangular mel filterbank, holding the weight of each spectrum bin for each of the `n_mels` bands.
+pub fn mel_filterbank(n_mels: usize, fft_size: usize, sample_rate: u32) -> Vec<Vec<f32>> {
+    let edges = mel_band_edges(n_mels, sample_rate);
+    let bin_hz = sample_rate as f32 / fft_size as f32;
+
+    edges
+        .windows(3)
+        .map(|band| {
+            let (low, center, high) = (band[0], band[1], band[2]);
+            (0..fft_size / 2 + 1)
+                .map(|bin| {
+                    let hz = bin as f32 * bin_hz;
+                    if hz <= low || hz >= high {
+                        0.0
+                    } else if hz <= center {
+                        (hz - low) / (center - low)
+                    } else {
+                        (high - hz) / (high - center)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    Major,
+    Minor,
+}
+
+/// A musical key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Key {
+    /// Pitch class of the tonic, from 0 for C up to 11 for B
+    pub tonic: u8,
+    pub mode: Mode,
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mode = match self.mode {
+            Mode::Major => "major",
+            Mode::Minor => "minor",
+        };
+        write!(f, "{} {}", PITCH_CLASS_NAMES[self.tonic as usize], mode)
+    }
+}
+
+/// Energy of each pitch class (C, C#, .., B) over all the frames of `spectrogram`.
+pub fn chroma(spectrogram: &[Vec<f32>], fft_size: usize, sample_rate: u32) -> [f32; 12] {
+    let bin_hz = sample_rate as f32 / fft_size as f32;
+    let mut chroma = [0f32; 12];
+
+    for magnitudes in spectrogram {
+        for (bin, magnitude) in magnitudes.iter().enumerate() {
+            let hz = bin as f32 * bin_hz;
+            if !(CHROMA_MIN_HZ..=CHROMA_MAX_HZ).contains(&hz) {
+                continue;
+            }
+            // Semitones away from A4, A being pitch class 9
+            let semitones = (12.0 * (hz / 440.0).log2()).round() as i32;
+            chroma[(semitones + 9).rem_euclid(12) as usize] += magnitude * magnitude;
+        }
+    }
+
+    chroma
+}
+
+/// Pearson correlation of two profiles, 0 when either is flat.
+fn profile_correlation(a: &[f32; 12], b: &[f32; 12]) -> f32 {
+    let mean_a = a.iter().sum::<f32>() / 12.0;
+    let mean_b = b.iter().sum::<f32>() / 12.0;
+    let (mut ab, mut aa, mut bb) = (0f32, 0f32, 0f32);
+    for (x, y) in a.iter().zip(b) {
+        ab += (x - mean_a) * (y - mean_b);
+        aa += (x - mean_a) * (x - mean_a);
+        bb += (y - mean_b) * (y - mean_b);
+    }
+
+    if aa <= f32::EPSILON || bb <= f32::EPSILON {
+        return 0.0;
+    }
+    ab / (aa * bb).sqrt()
+}
+
+/// Key whose Krumhansl-Kessler profile correlates best with `chroma`, along with the correlation.
+pub fn best_key(chroma: &[f32; 12]) -> (Key, f32) {
+    let mut best = (
+        Key {
+            tonic: 0,
+            mode: Mode::Major,
+        },
+        f32::MIN,
+    );
+
+    for tonic in 0..12 {
+        for (mode, profile) in [(Mode::Major, MAJOR_PROFILE), (Mode::Minor, MINOR_PROFILE)] {
+            let mut rotated = profile;
+            rotated.rotate_right(tonic);
+            let correlation = profile_correlation(chroma, &rotated);
+            if correlation > best.1 {
+                best = (
+                    Key {
+                        tonic: tonic as u8,
+                        mode,
+                    },
+                    correlation,
+                );
+            }
+        }
+    }
+
+    best
+}
+
+impl MonoBuffer {
+    /// Magnitude spectrum of each Hann windowed frame of `fft_size` samples, `hop` samples apart.
+    ///
+    /// Only complete frames of the written region are analysed, each holding `fft_size / 2 + 1` bins.
+    /// There are none when `fft_size` or `hop` is zero.
+    pub fn spectrogram(&self, fft_size: usize, hop: usize) -> Vec<Vec<f32>> {
+        if fft_size == 0 || hop == 0 {
+            return Vec::new();
+        }
+
+        let samples = self.slice(0, self.cursor());
+        let fft = RealFftPlanner::<f32>::new().plan_fft_forward(fft_size);
+        let window: Vec<f32> = (0..fft_size)
+            .map(|i| 0.5 - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / fft_size as f32).cos())
+            .collect();
+
+        let mut input = fft.make_input_vec();
+        let mut spectrum = fft.make_output_vec();
+        let frames = if samples.len() < fft_size {
+            0
+        } else {
+            (samples.len() - fft_size) / hop + 1
+        };
+
+        (0..frames)
+            .map(|frame| frame * hop)
+            .map(|start| {
+                for (i, sample) in input.iter_mut().enumerate() {
+                    *sample = samples[start + i] * window[i];
+                }
+                fft.process(&mut input, &mut spectrum)
+                    .expect("buffers are sized by the planner");
+                spectrum.iter().map(|bin| bin.norm()).collect()
+            })
+            .collect()
+    }
+
+    /// Energy of each of the `n_mels` mel bands for every frame of `spectrogram`.
+    ///
+    /// The standard front-end of audio ML, MFCCs are a DCT away.
+    pub fn mel_spectrogram(
+        &self,
+        fft_size: usize,
+        hop: usize,
+        n_mels: usize,
+        sample_rate: u32,
+    ) -> Vec<Vec<f32>> {
+        let filterbank = mel_filterbank(n_mels, fft_size, sample_rate);
+
+        self.spectrogram(fft_size, hop)
+            .iter()
+            .map(|magnitudes| {
+                filterbank
+                    .iter()
+                    .map(|weights| {
+                        weights
+                            .iter()
+                            .zip(magnitudes)
+                            .map(|(weight, magnitude)| weight * magnitude * magnitude)
+                            .sum()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl StereoBuffer {
+    /// Estimate the predominant key of the written content, along with a confidence between 0 and 1.
+    ///
+    /// The chroma of both channels mixed together is correlated against the Krumhansl-Kessler
+    /// key profiles. Returns `None` when even the best key correlates poorly, as atonal or
+    /// percussive content does.
+    pub fn estimate_key(&self, sample_rate: u32) -> Option<(Key, f32)> {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut mixed = MonoBuffer::new(left.len());
+        mixed.append_slice(
+            &left
+                .iter()
+                .zip(right)
+                .map(|(l, r)| 0.5 * (l + r))
+                .collect::<Vec<_>>(),
+        );
+
+        let spectrogram = mixed.spectrogram(KEY_FFT_SIZE, KEY_HOP);
+        let (key, confidence) = best_key(&chroma(&spectrogram, KEY_FFT_SIZE, sample_rate));
+
+        if confidence < KEY_CONFIDENCE_THRESHOLD {
+            return None;
+        }
+        Some((key, confidence))
+    }
+}
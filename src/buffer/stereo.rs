@@ -1,18 +1,172 @@
-use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Layout, Signal};
+use symphonia::core::audio::{AudioBuffer, AudioBufferRef, Channels, Layout, Signal};
+
+use super::{
+    analysis::{classify_content, BufferStats, ContentClass, StreamingStats},
+    error::BufferError,
+    mono::MonoBuffer,
+    utils::{uniform_audio_buffer, uniform_s16_audio_buffer, uniform_s32_audio_buffer},
+    Buffer, BufferLayout,
+};
+
+/// Correlation below which the channels are considered to be wired out of phase
+const POLARITY_INVERSION_THRESHOLD: f32 = -0.7;
+
+/// ITU-R BS.775 gain (-3dB) of the centre and surround channels folded into left and right
+const DOWNMIX_GAIN: f32 = std::f32::consts::FRAC_1_SQRT_2;
+
+/// Gains of a single source channel into the left and right channels, when downmixing to stereo.
+///
+/// Like the ITU downmix, the LFE channel is dropped, as are channels it does not account for.
+fn downmix_gains(channel: Channels) -> (f32, f32) {
+    match channel {
+        Channels::FRONT_LEFT => (1.0, 0.0),
+        Channels::FRONT_RIGHT => (0.0, 1.0),
+        Channels::FRONT_CENTRE => (DOWNMIX_GAIN, DOWNMIX_GAIN),
+        Channels::REAR_LEFT | Channels::SIDE_LEFT => (DOWNMIX_GAIN, 0.0),
+        Channels::REAR_RIGHT | Channels::SIDE_RIGHT => (0.0, DOWNMIX_GAIN),
+        _ => (0.0, 0.0),
+    }
+}
+
+/// Fold planar surround channels into left and right, `positions` being the position of each.
+fn downmix(channels: &[&[f32]], positions: &[Channels]) -> (Vec<f32>, Vec<f32>) {
+    let frames = channels.first().map_or(0, |channel| channel.len());
+    let mut left = vec![0f32; frames];
+    let mut right = vec![0f32; frames];
+
+    for (channel, position) in channels.iter().zip(positions) {
+        let (left_gain, right_gain) = downmix_gains(*position);
+        for ((l, r), sample) in left.iter_mut().zip(right.iter_mut()).zip(*channel) {
+            *l += sample * left_gain;
+            *r += sample * right_gain;
+        }
+    }
+
+    (left, right)
+}
 
-use super::{mono::MonoBuffer, utils::uniform_audio_buffer, Buffer, BufferLayout};
+/// Channel order of 5.1 content in SMPTE order: L, R, C, LFE, Ls, Rs
+const SMPTE_5_1: [Channels; 6] = [
+    Channels::FRONT_LEFT,
+    Channels::FRONT_RIGHT,
+    Channels::FRONT_CENTRE,
+    Channels::LFE1,
+    Channels::REAR_LEFT,
+    Channels::REAR_RIGHT,
+];
+
+/// Channel order of 5.1 content in film order: L, C, R, Ls, Rs, LFE
+const FILM_5_1: [Channels; 6] = [
+    Channels::FRONT_LEFT,
+    Channels::FRONT_CENTRE,
+    Channels::FRONT_RIGHT,
+    Channels::REAR_LEFT,
+    Channels::REAR_RIGHT,
+    Channels::LFE1,
+];
 
 /// Stereo channels
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Channel {
     Left = 0,
     Right = 1,
 }
 
+/// Maps the channels of the decoded content to the output channels.
+///
+/// Keeps channel assignment consistent across sources which label their channels differently.
+/// The 5.1 presets tell the order of six channel sources, whatever their labels, and are applied
+/// before the downmix. Sources with another channel count are read as with `Identity`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ChannelMap {
+    /// Keep the source channel order.
+    #[default]
+    Identity,
+    /// Exchange the left and right channels.
+    SwapLR,
+    /// Six channels ordered L, R, C, LFE, Ls, Rs (SMPTE, as in WAV and FLAC).
+    Smpte5_1,
+    /// Six channels ordered L, C, R, Ls, Rs, LFE (film, as in AC-3 and DTS productions).
+    Film5_1,
+}
+
+impl ChannelMap {
+    /// Indexes of the planar channels read into the left and right channels, out of `channels`.
+    ///
+    /// Mono is read into both, and only the first two of more channels are read, unless they are
+    /// downmixed according to `positions`.
+    fn sources(self, channels: usize) -> (usize, usize) {
+        match (channels, self) {
+            (1, _) => (0, 0),
+            (_, ChannelMap::SwapLR) => (1, 0),
+            (_, ChannelMap::Identity | ChannelMap::Smpte5_1 | ChannelMap::Film5_1) => (0, 1),
+        }
+    }
+
+    /// Positions of the planar channels of a source of `channels` channels, when set by the map.
+    fn positions(self, channels: usize) -> Option<&'static [Channels]> {
+        let positions: &[Channels] = match self {
+            ChannelMap::Smpte5_1 => &SMPTE_5_1,
+            ChannelMap::Film5_1 => &FILM_5_1,
+            ChannelMap::Identity | ChannelMap::SwapLR => return None,
+        };
+        Some(positions).filter(|positions| positions.len() == channels)
+    }
+
+    /// Remap the frames written to `buffer` from `from` onwards.
+    ///
+    /// Surround presets are applied while appending, before the downmix, they leave it untouched.
+    pub(crate) fn apply(self, buffer: &mut StereoBuffer, from: usize) {
+        match self {
+            ChannelMap::Identity | ChannelMap::Smpte5_1 | ChannelMap::Film5_1 => {}
+            ChannelMap::SwapLR => {
+                let (left, right) = buffer.slice_mut(from, buffer.cursor() - from);
+                left.swap_with_slice(right);
+            }
+        }
+    }
+}
+
+/// How `StereoBuffer::to_mono` mixes both channels into one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonoMix {
+    /// `(L + R) / 2`, the mid component
+    Average,
+    /// `L + R`, which may exceed full scale
+    Sum,
+    Left,
+    Right,
+}
+
 pub struct StereoBuffer {
     pub left: MonoBuffer,
     pub right: MonoBuffer,
 }
 
+/// Owned copy of the written region of a `StereoBuffer`, see `StereoBuffer::snapshot`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BufferSnapshot {
+    left: Vec<f32>,
+    right: Vec<f32>,
+}
+
+impl BufferSnapshot {
+    /// Frames held by the snapshot, which is the cursor it restores.
+    pub fn frames(&self) -> usize {
+        self.left.len()
+    }
+}
+
+/// Mid component of a frame
+fn mid(left: f32, right: f32) -> f32 {
+    0.5 * (left + right)
+}
+
+/// Side component of a frame
+fn side(left: f32, right: f32) -> f32 {
+    0.5 * (left - right)
+}
+
 impl StereoBuffer {
     pub fn new(capacity: usize) -> Self {
         Self {
@@ -25,6 +179,14 @@ impl StereoBuffer {
         (self.left.slice(start, len), self.right.slice(start, len))
     }
 
+    /// Same as `slice`, failing instead of panicking when the region exceeds the capacity.
+    pub fn try_slice(&self, start: usize, len: usize) -> Result<(&[f32], &[f32]), BufferError> {
+        Ok((
+            self.left.try_slice(start, len)?,
+            self.right.try_slice(start, len)?,
+        ))
+    }
+
     pub fn slice_mut(&mut self, start: usize, len: usize) -> (&mut [f32], &mut [f32]) {
         (
             self.left.slice_mut(start, len),
@@ -32,6 +194,26 @@ impl StereoBuffer {
         )
     }
 
+    /// The buffer of a single channel.
+    pub fn channel(&self, channel: Channel) -> &MonoBuffer {
+        match channel {
+            Channel::Left => &self.left,
+            Channel::Right => &self.right,
+        }
+    }
+
+    pub fn channel_mut(&mut self, channel: Channel) -> &mut MonoBuffer {
+        match channel {
+            Channel::Left => &mut self.left,
+            Channel::Right => &mut self.right,
+        }
+    }
+
+    /// Same as `slice`, for a single channel.
+    pub fn channel_slice(&self, channel: Channel, start: usize, len: usize) -> &[f32] {
+        self.channel(channel).slice(start, len)
+    }
+
     pub fn as_slice(&self) -> (&[f32], &[f32]) {
         (self.left.as_slice(), self.right.as_slice())
     }
@@ -40,6 +222,73 @@ impl StereoBuffer {
         (self.left.as_slice_mut(), self.right.as_slice_mut())
     }
 
+    /// Iterate over the left and right samples of each frame, over the whole capacity.
+    pub fn iter_mut(
+        &mut self,
+    ) -> std::iter::Zip<std::slice::IterMut<'_, f32>, std::slice::IterMut<'_, f32>> {
+        debug_assert_eq!(self.left.capacity(), self.right.capacity());
+
+        self.left.iter_mut().zip(self.right.iter_mut())
+    }
+
+    /// Copy `len` written frames starting at `start` of both channels into a new buffer.
+    ///
+    /// The source buffer is left untouched, the new buffer's cursor is set to `len`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the requested region exceeds the written frames.
+    pub fn extract(&self, start: usize, len: usize) -> StereoBuffer {
+        Self {
+            left: self.left.extract(start, len),
+            right: self.right.extract(start, len),
+        }
+    }
+
+    /// Insert `frames` of silence before the written frames of both channels.
+    pub fn prepend_silence(&mut self, frames: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.prepend_silence(frames);
+        self.right.prepend_silence(frames);
+    }
+
+    /// Truncate the written frames of both channels to `frames`, or pad them with silence up to `frames`.
+    pub fn resize(&mut self, frames: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.resize(frames);
+        self.right.resize(frames);
+    }
+
+    /// Truncate or pad the written content with silence so it lasts exactly `secs` seconds.
+    ///
+    /// The frame count is rounded to the nearest integer.
+    pub fn trim_to_seconds(&mut self, secs: f32, sample_rate: u32) {
+        let frames = (secs as f64 * sample_rate as f64).round() as usize;
+        self.resize(frames);
+    }
+
+    /// Repeat the written frames of both channels until `target_frames` are written.
+    ///
+    /// See `MonoBuffer::loop_extend` for the crossfade, truncation and empty buffer handling.
+    pub fn loop_extend(&mut self, target_frames: usize, crossfade: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.loop_extend(target_frames, crossfade);
+        self.right.loop_extend(target_frames, crossfade);
+    }
+
+    /// Blend the last `frames` written frames of both channels into the first ones and drop them.
+    ///
+    /// See `MonoBuffer::crossfade_loop`.
+    pub fn crossfade_loop(&mut self, frames: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.crossfade_loop(frames);
+        self.right.crossfade_loop(frames);
+    }
+
     /// Same as append_slice, but independent for each channel.
     pub fn append_slices(&mut self, left: &[f32], right: &[f32]) {
         debug_assert_eq!(self.left.capacity(), self.right.capacity());
@@ -49,6 +298,39 @@ impl StereoBuffer {
         self.right.append_slice(right);
     }
 
+    /// Append a slice to a single channel, letting its cursor drift from the other one.
+    ///
+    /// Every other method expects both cursors to match, call `sync_cursors` before using them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is larger than the capacity left in the channel.
+    pub fn fill_channel(&mut self, channel: Channel, slice: &[f32]) {
+        self.channel_mut(channel).append_slice(slice);
+    }
+
+    /// Pad the channel lagging behind with silence, so both cursors match again.
+    ///
+    /// Returns the cursor of both channels.
+    pub fn sync_cursors(&mut self) -> usize {
+        let cursor = std::cmp::max(self.left.cursor(), self.right.cursor());
+        self.left.resize(cursor);
+        self.right.resize(cursor);
+        cursor
+    }
+
+    /// Same as append_slices, but only appends if both slices fit in the remaining capacity.
+    ///
+    /// Returns `false` without appending anything otherwise.
+    pub fn try_append_slices(&mut self, left: &[f32], right: &[f32]) -> bool {
+        if self.left.overflow_on(left.len()) > 0 || self.right.overflow_on(right.len()) > 0 {
+            return false;
+        }
+
+        self.append_slices(left, right);
+        true
+    }
+
     /// Append a slice to all channels, and fill overflow with unappendable content.
     pub fn append_slices_overflow(
         &mut self,
@@ -65,7 +347,36 @@ impl StereoBuffer {
         self.right.append_slice_overflow(right, &mut overflow.right);
     }
 
+    /// Append an audio buffer, filling overflow with unappendable content.
+    ///
+    /// Mono content is duplicated into both channels. Surround content is downmixed with the
+    /// ITU coefficients: the centre and surrounds at -3dB, and the LFE dropped.
     pub fn append_audio_buffer(&mut self, buffer: &AudioBuffer<f32>, overflow: &mut StereoBuffer) {
+        self.append_audio_buffer_from(buffer, 0, overflow);
+    }
+
+    /// Same as append_audio_buffer, but the first `offset` frames of the audio buffer are dropped.
+    ///
+    /// Mono and stereo audio buffers are read in place, nothing is copied besides the appended frames.
+    pub fn append_audio_buffer_from(
+        &mut self,
+        buffer: &AudioBuffer<f32>,
+        offset: usize,
+        overflow: &mut StereoBuffer,
+    ) {
+        self.append_audio_buffer_mapped(buffer, offset, ChannelMap::Identity, overflow);
+    }
+
+    /// Same as append_audio_buffer_from, surround content being downmixed according to `map`.
+    ///
+    /// Mono and stereo content is appended as is, see `ChannelMap` to remap it.
+    pub fn append_audio_buffer_mapped(
+        &mut self,
+        buffer: &AudioBuffer<f32>,
+        offset: usize,
+        map: ChannelMap,
+        overflow: &mut StereoBuffer,
+    ) {
         debug_assert_eq!(self.left.capacity(), self.right.capacity());
         debug_assert_eq!(self.left.cursor(), self.right.cursor());
         debug_assert_eq!(overflow.left.capacity(), overflow.right.capacity());
@@ -74,57 +385,563 @@ impl StereoBuffer {
         let spec = buffer.spec();
 
         if spec.channels == Layout::Mono.into_channels() {
-            let mono_buf = buffer.chan(0);
-            self.append_slices_overflow(mono_buf, mono_buf, overflow);
+            self.append_planar(&[&buffer.chan(0)[offset..]], ChannelMap::Identity, overflow);
             return;
         }
 
         if spec.channels == Layout::Stereo.into_channels() {
-            self.append_slices_overflow(buffer.chan(0), buffer.chan(1), overflow);
+            let planes = [&buffer.chan(0)[offset..], &buffer.chan(1)[offset..]];
+            self.append_planar(&planes, ChannelMap::Identity, overflow);
             return;
         }
 
-        unimplemented!("Only mono and stereo audio buffers are supported")
+        let planes: Vec<&[f32]> = (0..spec.channels.count())
+            .map(|channel| &buffer.chan(channel)[offset..])
+            .collect();
+        let labels: Vec<Channels> = spec.channels.iter().collect();
+        let positions = map.positions(planes.len()).unwrap_or(&labels);
+        let (left, right) = downmix(&planes, positions);
+        self.append_planar(&[&left, &right], ChannelMap::Identity, overflow);
+    }
+
+    /// Append planar channels according to `map`, filling overflow with unappendable content.
+    ///
+    /// A single channel is duplicated into both, see `ChannelMap` for more channels.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `channels` is empty.
+    pub fn append_planar(
+        &mut self,
+        channels: &[&[f32]],
+        map: ChannelMap,
+        overflow: &mut StereoBuffer,
+    ) {
+        assert!(!channels.is_empty(), "at least one channel is required");
+        if let Some(positions) = map.positions(channels.len()) {
+            let (left, right) = downmix(channels, positions);
+            self.append_slices_overflow(&left, &right, overflow);
+            return;
+        }
+
+        let (left, right) = map.sources(channels.len());
+        self.append_slices_overflow(channels[left], channels[right], overflow);
+    }
+
+    /// Samples of each channel written but not read yet, see `MonoBuffer::unread`.
+    pub fn unread(&self) -> usize {
+        debug_assert_eq!(self.left.read_cursor(), self.right.read_cursor());
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.unread()
+    }
+
+    /// Samples of each channel `write_wrapping` can write, see `MonoBuffer::ring_space`.
+    pub fn ring_space(&self) -> usize {
+        debug_assert_eq!(self.left.read_cursor(), self.right.read_cursor());
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.ring_space()
+    }
+
+    /// Write both channels to the ring, see `MonoBuffer::write_wrapping`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices differ in length.
+    pub fn write_wrapping(&mut self, left: &[f32], right: &[f32]) -> usize {
+        assert_eq!(left.len(), right.len());
+        debug_assert_eq!(self.left.read_cursor(), self.right.read_cursor());
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.right.write_wrapping(right);
+        self.left.write_wrapping(left)
+    }
+
+    /// Read both channels from the ring, see `MonoBuffer::read_wrapping`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices differ in length.
+    pub fn read_wrapping(&mut self, left: &mut [f32], right: &mut [f32]) -> usize {
+        assert_eq!(left.len(), right.len());
+        debug_assert_eq!(self.left.read_cursor(), self.right.read_cursor());
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.right.read_wrapping(right);
+        self.left.read_wrapping(left)
     }
 
     pub fn append_audio_buffer_ref(
         &mut self,
         buffer: &AudioBufferRef,
         overflow: &mut StereoBuffer,
+    ) {
+        self.append_audio_buffer_ref_mapped(buffer, ChannelMap::Identity, overflow);
+    }
+
+    /// Same as append_audio_buffer_ref, surround content being downmixed according to `map`.
+    pub fn append_audio_buffer_ref_mapped(
+        &mut self,
+        buffer: &AudioBufferRef,
+        map: ChannelMap,
+        overflow: &mut StereoBuffer,
     ) {
         match buffer {
-            AudioBufferRef::F32(input) => self.append_audio_buffer(input, overflow),
+            AudioBufferRef::F32(input) => self.append_audio_buffer_mapped(input, 0, map, overflow),
             AudioBufferRef::U8(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::U16(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::U24(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::U32(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::S8(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::S16(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_s16_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::S24(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::S32(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_s32_audio_buffer(input), 0, map, overflow)
             }
             AudioBufferRef::F64(input) => {
-                self.append_audio_buffer(&uniform_audio_buffer(input), overflow)
+                self.append_audio_buffer_mapped(&uniform_audio_buffer(input), 0, map, overflow)
             }
         }
     }
 
-    // TODO iterate over buffer by Channel parameter
+    /// Convert the written region from left/right to mid/side, in place.
+    ///
+    /// The mid is stored in the left channel and the side in the right channel.
+    pub fn encode_mid_side(&mut self) {
+        let (left, right) = self.slice_mut(0, self.cursor());
+        for (l, r) in left.iter_mut().zip(right.iter_mut()) {
+            let (m, s) = (mid(*l, *r), side(*l, *r));
+            *l = m;
+            *r = s;
+        }
+    }
+
+    /// The side component of the written region, `(L - R) / 2`, leaving the buffer untouched.
+    ///
+    /// Holds only what differs between the channels, such as stereo-only content or encoding artifacts.
+    pub fn side_channel(&self) -> MonoBuffer {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut side_channel = MonoBuffer::new(left.len());
+        side_channel.append_slice(
+            &left
+                .iter()
+                .zip(right)
+                .map(|(l, r)| side(*l, *r))
+                .collect::<Vec<_>>(),
+        );
+        side_channel
+    }
+
+    /// Mix the written region down to a single channel, leaving the buffer untouched.
+    ///
+    /// The counterpart of the duplication of mono content into both channels.
+    pub fn to_mono(&self, mix: MonoMix) -> MonoBuffer {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut mono = MonoBuffer::new(left.len());
+        match mix {
+            MonoMix::Left => mono.append_slice(left),
+            MonoMix::Right => mono.append_slice(right),
+            MonoMix::Average | MonoMix::Sum => {
+                let mixed: Vec<f32> = left
+                    .iter()
+                    .zip(right)
+                    .map(|(l, r)| match mix {
+                        MonoMix::Average => mid(*l, *r),
+                        _ => l + r,
+                    })
+                    .collect();
+                mono.append_slice(&mixed);
+            }
+        }
+        mono
+    }
+
+    /// Convert the written region from mid/side back to left/right, in place.
+    ///
+    /// The inverse of `encode_mid_side`.
+    pub fn decode_mid_side(&mut self) {
+        let (mid, side) = self.slice_mut(0, self.cursor());
+        for (m, s) in mid.iter_mut().zip(side.iter_mut()) {
+            let (left, right) = (*m + *s, *m - *s);
+            *m = left;
+            *s = right;
+        }
+    }
+
+    /// Scale the stereo width of the written region.
+    ///
+    /// `1.0` leaves the content unchanged, `0.0` collapses it to mono and values above `1.0` widen it.
+    /// The width is not clamped, so wide settings may clip.
+    pub fn set_width(&mut self, width: f32) {
+        self.encode_mid_side();
+        self.right
+            .slice_mut(0, self.cursor())
+            .iter_mut()
+            .for_each(|side| *side *= width);
+        self.decode_mid_side();
+    }
+
+    /// Copy the written region, for instance to undo a destructive edit with `restore`.
+    ///
+    /// Unlike cloning the buffer, the unused capacity is not copied.
+    pub fn snapshot(&self) -> BufferSnapshot {
+        let (left, right) = self.slice(0, self.cursor());
+        BufferSnapshot {
+            left: left.to_vec(),
+            right: right.to_vec(),
+        }
+    }
+
+    /// Replace the content with the one of `snapshot`, including its cursor.
+    ///
+    /// The buffer is reallocated to fit the snapshot exactly, so its capacity may differ from the
+    /// one it had when the snapshot was taken.
+    pub fn restore(&mut self, snapshot: BufferSnapshot) {
+        let frames = snapshot.frames();
+        *self = StereoBuffer::new(frames);
+        self.append_slices(&snapshot.left, &snapshot.right);
+    }
+
+    /// Copy the written region into a flat `[channels, frames]` matrix, as ML frameworks expect it.
+    ///
+    /// Returns the channel count along with the data. The layout is planar, not interleaved:
+    /// all the frames of the left channel are followed by all the frames of the right channel.
+    pub fn to_matrix(&self) -> (usize, Vec<f32>) {
+        let (left, right) = self.slice(0, self.cursor());
+        (2, [left, right].concat())
+    }
+
+    /// Split `[L0, R0, L1, R1, ...]` frames into a new buffer, with its cursor set to `data.len() / 2`.
+    ///
+    /// A trailing sample of an odd length `data` belongs to no complete frame, and is dropped.
+    pub fn from_interleaved(data: &[f32]) -> StereoBuffer {
+        let (left, right): (Vec<f32>, Vec<f32>) = data
+            .chunks_exact(2)
+            .map(|frame| (frame[0], frame[1]))
+            .unzip();
+
+        let mut buffer = StereoBuffer::new(left.len());
+        buffer.append_slices(&left, &right);
+        buffer
+    }
+
+    /// Copy the whole buffer into `[L0, R0, L1, R1, ...]` frames, as audio hosts and encoders expect it.
+    ///
+    /// Covers the `channel_capacity`, not only the written region. Frames past the cursor hold
+    /// whatever the buffer holds there, pad the buffer with silence first if they matter.
+    pub fn to_interleaved(&self) -> Vec<f32> {
+        let mut out = vec![0f32; self.channel_capacity() * 2];
+        self.interleave_into(&mut out);
+        out
+    }
+
+    /// Same as `to_interleaved`, writing up to `out.len() / 2` frames into `out`.
+    ///
+    /// Returns the number of frames written. A trailing sample of an odd length `out` is left untouched.
+    pub fn interleave_into(&self, out: &mut [f32]) -> usize {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        let frames = std::cmp::min(out.len() / 2, self.channel_capacity());
+        let (left, right) = self.slice(0, frames);
+        for ((frame, l), r) in out.chunks_exact_mut(2).zip(left).zip(right) {
+            frame[0] = *l;
+            frame[1] = *r;
+        }
+        frames
+    }
+
+    /// Same as `to_interleaved`, covering only the written region.
+    pub fn as_interleaved_vec(&self) -> Vec<f32> {
+        let mut out = vec![0f32; self.cursor() * 2];
+        self.interleave_into(&mut out);
+        out
+    }
+
+    /// Same as `as_interleaved_vec`, writing up to `out_len_frames` frames through a raw pointer,
+    /// for hosts pulling decoded audio across an FFI boundary.
+    ///
+    /// Returns the number of frames written.
+    ///
+    /// # Safety
+    ///
+    /// Unless `out_len_frames` is 0, `out` must be non null, aligned, and valid for writes of
+    /// `2 * out_len_frames` f32 samples, none of which may belong to this buffer.
+    pub unsafe fn fill_interleaved(&self, out: *mut f32, out_len_frames: usize) -> usize {
+        let frames = std::cmp::min(out_len_frames, self.cursor());
+        if frames == 0 {
+            return 0;
+        }
+        // SAFETY: upheld by the caller, `frames` is at most `out_len_frames`
+        let out = std::slice::from_raw_parts_mut(out, frames * 2);
+        self.interleave_into(out)
+    }
+
+    /// Same as `to_matrix`, as an `ndarray::Array2` of shape `[channels, frames]`.
+    #[cfg(feature = "ndarray")]
+    pub fn to_array(&self) -> ndarray::Array2<f32> {
+        let (channels, data) = self.to_matrix();
+        ndarray::Array2::from_shape_vec((channels, self.cursor()), data)
+            .expect("planar data matches the [channels, frames] shape")
+    }
+
+    /// Ranges of silence at least `min_frames` long over the written region, as `(start, end)` frames.
+    ///
+    /// A frame is silent when the magnitude of both channels is at most `threshold`.
+    /// The end of each range is exclusive.
+    pub fn silence_regions(&self, threshold: f32, min_frames: usize) -> Vec<(usize, usize)> {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut regions = Vec::new();
+        let mut start = None;
+
+        for (i, (l, r)) in left.iter().zip(right).enumerate() {
+            let silent = l.abs() <= threshold && r.abs() <= threshold;
+            match (silent, start) {
+                (true, None) => start = Some(i),
+                (false, Some(from)) => {
+                    if i - from >= min_frames {
+                        regions.push((from, i));
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(from) = start {
+            if left.len() - from >= min_frames {
+                regions.push((from, left.len()));
+            }
+        }
+
+        regions
+    }
+
+    /// Drop the silent frames leading and trailing the written region, then align the buffer to
+    /// `alignment` frames.
+    ///
+    /// Frames are silent as defined by `silence_regions`. The remaining content is moved to the
+    /// start of the buffer, which is trimmed to it before being aligned. The aligned tail is left
+    /// unwritten, pad it with silence if needed. A silent buffer ends up empty.
+    ///
+    /// Returns the number of leading frames dropped.
+    pub fn trim_silence(&mut self, threshold: f32, alignment: usize) -> usize {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        let (left, right) = self.slice(0, self.cursor());
+        let audible = |(l, r): (&f32, &f32)| l.abs() > threshold || r.abs() > threshold;
+        let start = left
+            .iter()
+            .zip(right)
+            .position(audible)
+            .unwrap_or(left.len());
+        let end = left
+            .iter()
+            .zip(right)
+            .rposition(audible)
+            .map_or(start, |last| last + 1);
+
+        for channel in [&mut self.left, &mut self.right] {
+            channel.as_slice_mut().copy_within(start..end, 0);
+            channel.resize(end - start);
+            channel.trim();
+            channel.align_to(alignment);
+        }
+        start
+    }
+
+    /// Multiply the written frames of both channels by `gain`, without clamping them.
+    ///
+    /// Returns whether any sample ended up outside `[-1, 1]`, which would clip on playback.
+    pub fn apply_gain(&mut self, gain: f32) -> bool {
+        self.apply_gain_lr(gain, gain)
+    }
+
+    /// Same as `apply_gain`, with a distinct gain per channel, such as for balance adjustments.
+    pub fn apply_gain_lr(&mut self, left_gain: f32, right_gain: f32) -> bool {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        let left_clipped = self.left.apply_gain(left_gain);
+        let right_clipped = self.right.apply_gain(right_gain);
+        left_clipped || right_clipped
+    }
+
+    /// Multiply each written frame of both channels by the gain at the same position of `envelope`.
+    pub fn apply_gain_envelope(&mut self, envelope: &[f32]) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.apply_gain_envelope(envelope);
+        self.right.apply_gain_envelope(envelope);
+    }
+
+    /// Modulate the amplitude of the written frames with a sine LFO of `rate_hz`.
+    ///
+    /// The gain follows `1 - depth * (1 - cos(2π * rate_hz * t)) / 2`, starting at unity and dipping
+    /// down to `1 - depth` once per cycle, so a `depth` of 1 fully silences the troughs. The LFO
+    /// runs continuously over the whole written region.
+    pub fn tremolo(&mut self, rate_hz: f32, depth: f32, sample_rate: u32) {
+        let depth = depth.clamp(0.0, 1.0) as f64;
+        let phase_step = std::f64::consts::TAU * rate_hz as f64 / sample_rate as f64;
+        // Wrapping the phase keeps it precise however long the buffer is
+        let mut phase = 0f64;
+        let written = self.cursor();
+        for (left, right) in self.iter_mut().take(written) {
+            let gain = (1.0 - depth * 0.5 * (1.0 - phase.cos())) as f32;
+            *left *= gain;
+            *right *= gain;
+            phase = (phase + phase_step) % std::f64::consts::TAU;
+        }
+    }
+
+    /// Linearly fade in the first `frames` written frames of both channels.
+    pub fn fade_in(&mut self, frames: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.fade_in(frames);
+        self.right.fade_in(frames);
+    }
+
+    /// Linearly fade out the last `frames` written frames of both channels.
+    pub fn fade_out(&mut self, frames: usize) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.fade_out(frames);
+        self.right.fade_out(frames);
+    }
+
+    /// Scale both channels so that the loudest written sample reaches `target`.
+    ///
+    /// Silent content is left untouched.
+    pub fn normalize_peak(&mut self, target: f32) {
+        let peak = self.stats().peak;
+        if peak > 0.0 {
+            self.apply_gain(target / peak);
+        }
+    }
+
+    /// Limit the written frames of both channels to `[-limit, limit]`.
+    pub fn clamp(&mut self, limit: f32) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.clamp(limit);
+        self.right.clamp(limit);
+    }
+
+    /// Whether the written regions of both buffers hold exactly the same samples, without tolerance.
+    ///
+    /// Meant for golden output tests, such as checking that a decoder upgrade does not change the
+    /// decoded content. Samples are compared with `==`, so content holding a NaN never equals
+    /// anything, replace NaNs beforehand.
+    pub fn bit_exact_eq(&self, other: &StereoBuffer) -> bool {
+        self.cursor() == other.cursor()
+            && self.slice(0, self.cursor()) == other.slice(0, other.cursor())
+    }
+
+    /// Correlation between both channels over the written region, between -1 and 1.
+    ///
+    /// 1 for identical channels, 0 for unrelated ones (or silence) and -1 for opposite ones.
+    pub fn correlation(&self) -> f32 {
+        let (left, right) = self.slice(0, self.cursor());
+        let (mut lr, mut ll, mut rr) = (0f64, 0f64, 0f64);
+        for (l, r) in left.iter().zip(right) {
+            let (l, r) = (*l as f64, *r as f64);
+            lr += l * r;
+            ll += l * l;
+            rr += r * r;
+        }
+
+        if ll == 0.0 || rr == 0.0 {
+            return 0.0;
+        }
+        (lr / (ll * rr).sqrt()) as f32
+    }
+
+    /// Whether one channel looks wired out of phase, which cancels out on mono fold-down.
+    ///
+    /// Only strongly negative correlations qualify, so genuinely wide content is not mistaken for it.
+    pub fn detect_polarity_inversion(&self) -> bool {
+        self.correlation() < POLARITY_INVERSION_THRESHOLD
+    }
+
+    /// Invert the right channel if `detect_polarity_inversion` holds.
+    pub fn fix_polarity_inversion(&mut self) {
+        if self.detect_polarity_inversion() {
+            self.right.apply_gain(-1.0);
+        }
+    }
+
+    /// RMS of the written region of the left and right channels.
+    pub fn rms(&self) -> (f32, f32) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        (self.left.rms(), self.right.rms())
+    }
+
+    /// Peak of the written region of the left and right channels.
+    pub fn peak(&self) -> (f32, f32) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        (self.left.peak(), self.right.peak())
+    }
+
+    /// DC offset of the written region of the left and right channels.
+    pub fn dc_offset(&self) -> (f32, f32) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        (self.left.dc_offset(), self.right.dc_offset())
+    }
+
+    /// Remove the DC offset of each channel independently, see `MonoBuffer::remove_dc`.
+    pub fn remove_dc(&mut self) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        self.left.remove_dc();
+        self.right.remove_dc();
+    }
+
+    /// Windowed RMS of the left and right channels, see `MonoBuffer::rms_windowed`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window_len` is 0.
+    pub fn rms_windowed(&self, window_len: usize) -> (Vec<f32>, Vec<f32>) {
+        debug_assert_eq!(self.left.cursor(), self.right.cursor());
+
+        (
+            self.left.rms_windowed(window_len),
+            self.right.rms_windowed(window_len),
+        )
+    }
+
+    /// Statistics of the written region of both channels.
+    pub fn stats(&self) -> BufferStats {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut stats = StreamingStats::new();
+        stats.push(left);
+        stats.push(right);
+        stats.finish()
+    }
+
+    /// Heuristically classify the written content as speech or music, see `analysis::classify_content`.
+    pub fn classify_content(&self, sample_rate: u32) -> ContentClass {
+        let (left, right) = self.slice(0, self.cursor());
+        let mixed: Vec<f32> = left.iter().zip(right).map(|(l, r)| mid(*l, *r)).collect();
+        classify_content(&mixed, sample_rate)
+    }
 }
 
 impl Buffer for StereoBuffer {
@@ -4,13 +4,12 @@ use symphonia::core::{
     sample::Sample,
 };
 
-/// Create a buffer with a given capacity and set its length to the same value.
+/// Create a buffer of `size` samples of silence.
+///
+/// Zeroed allocations are handed out by the allocator, so this costs about as much as leaving them
+/// uninitialized.
 pub fn buffer_with_size(size: usize) -> Vec<f32> {
-    let mut buffer = Vec::<f32>::with_capacity(size);
-    unsafe {
-        buffer.set_len(size);
-    }
-    buffer
+    vec![0f32; size]
 }
 
 /// Convert any AudioBuffer<S> into an AudioBuffer<f32> by copying and converting each sample.
@@ -26,6 +25,47 @@ where
     return converted;
 }
 
+/// Same as `uniform_audio_buffer` for i16 samples, vectorized with the `simd` feature.
+pub fn uniform_s16_audio_buffer(input: &AudioBuffer<i16>) -> AudioBuffer<f32> {
+    #[cfg(feature = "simd")]
+    {
+        vectorized_audio_buffer(input, super::simd::s16_to_f32)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        uniform_audio_buffer(input)
+    }
+}
+
+/// Same as `uniform_audio_buffer` for i32 samples, vectorized with the `simd` feature.
+pub fn uniform_s32_audio_buffer(input: &AudioBuffer<i32>) -> AudioBuffer<f32> {
+    #[cfg(feature = "simd")]
+    {
+        vectorized_audio_buffer(input, super::simd::s32_to_f32)
+    }
+    #[cfg(not(feature = "simd"))]
+    {
+        uniform_audio_buffer(input)
+    }
+}
+
+/// Convert each channel of `input` with `convert`.
+#[cfg(feature = "simd")]
+fn vectorized_audio_buffer<S: Sample>(
+    input: &AudioBuffer<S>,
+    convert: fn(&[S], &mut [f32]),
+) -> AudioBuffer<f32> {
+    use symphonia::core::audio::Signal;
+
+    let spec = *input.spec();
+    let mut converted = AudioBuffer::<f32>::new(input.capacity() as u64, spec);
+    converted.render_reserved(Some(input.frames()));
+    for channel in 0..spec.channels.count() {
+        convert(input.chan(channel), converted.chan_mut(channel));
+    }
+    converted
+}
+
 /// This will 100% clone
 pub fn convert_any_audio_buffer(buffer: &AudioBufferRef) -> AudioBuffer<f32> {
     match buffer {
@@ -35,9 +75,9 @@ pub fn convert_any_audio_buffer(buffer: &AudioBufferRef) -> AudioBuffer<f32> {
         AudioBufferRef::U24(input) => uniform_audio_buffer(input),
         AudioBufferRef::U32(input) => uniform_audio_buffer(input),
         AudioBufferRef::S8(input) => uniform_audio_buffer(input),
-        AudioBufferRef::S16(input) => uniform_audio_buffer(input),
+        AudioBufferRef::S16(input) => uniform_s16_audio_buffer(input),
         AudioBufferRef::S24(input) => uniform_audio_buffer(input),
-        AudioBufferRef::S32(input) => uniform_audio_buffer(input),
+        AudioBufferRef::S32(input) => uniform_s32_audio_buffer(input),
         AudioBufferRef::F64(input) => uniform_audio_buffer(input),
     }
 }
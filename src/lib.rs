@@ -1,5 +1,10 @@
 pub mod buffer;
 pub mod reader;
+#[cfg(feature = "hound")]
+pub mod transcode;
+
+#[cfg(feature = "hound")]
+pub use transcode::{transcode_to_wav, transcode_to_wav_dithered, Dither, WavFormat};
 
 pub mod prelude {
     pub use crate::{buffer::prelude::*, reader::prelude::*};
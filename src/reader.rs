@@ -1,26 +1,38 @@
+#[cfg(feature = "tokio")]
+pub mod async_reader;
+#[cfg(feature = "rayon")]
+pub mod batch;
 pub mod error;
 pub mod full_reader;
+pub mod lookahead_reader;
+pub mod mono_full_reader;
 pub mod prepare;
+pub mod scrub_reader;
+pub mod stream_reader;
 
-use self::prepare::{prepare_sample_reader, ReaderMeta};
-use crate::buffer::{stereo::StereoBuffer, Buffer};
+use self::prepare::{
+    prepare_media_source, prepare_memory_source, prepare_sample_reader, ReaderMeta,
+    SourceDescriptor,
+};
+pub use crate::buffer::stereo::ChannelMap;
+use crate::buffer::{
+    stereo::{Channel, StereoBuffer},
+    utils::convert_any_audio_buffer,
+    Buffer,
+};
 use error::*;
 
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
 use symphonia::core::{
-    audio::AudioBufferRef,
+    audio::{AudioBufferRef, Layout, SignalSpec},
     codecs::{Decoder, DecoderOptions},
     errors,
-    formats::{FormatOptions, FormatReader, Packet, Track},
-    meta::MetadataOptions,
+    formats::{FormatOptions, FormatReader, Packet, SeekMode, SeekTo, SeekedTo, Track},
+    io::{MediaSource, MediaSourceStream},
+    meta::{MetadataOptions, StandardTagKey},
+    units::Time,
 };
 
-macro_rules! symph_err {
-    ( $x:expr ) => {{
-        SampleDecodeError::LoadError(SampleLoadError::SymphoniaError($x))
-    }};
-}
-
 /// A thing you receive after you read and decode a packet.
 pub enum ReadingProjection {
     /// Samples read per channel.
@@ -28,14 +40,191 @@ pub enum ReadingProjection {
     EndReached,
 }
 
+/// What to do with the last slice of a buffer which is shorter than the host buffer.
+///
+/// This can only happen when the buffer was not aligned to the host buffer length.
+#[derive(Debug, Clone, Copy, Default)]
+pub enum PartialSlicePolicy {
+    /// Issue the shorter slice as is.
+    #[default]
+    Truncate,
+    /// Drop the shorter slice and start over from the beginning of the buffer.
+    Skip,
+}
+
+/// How a `SyncFullReader` grows its buffer when the frame count of the source is unknown.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GrowthPolicy {
+    /// Double the capacity.
+    #[default]
+    Double,
+    /// Multiply the capacity by the given factor, meant to be greater than 1.
+    Factor(f32),
+    /// Grow by the given number of frames per channel, which caps the transient overallocation.
+    FixedChunks(usize),
+}
+
+impl GrowthPolicy {
+    /// Frames per channel to add to a buffer of `capacity` frames per channel.
+    ///
+    /// Readers grow by at least a packet worth of frames regardless.
+    pub fn additional(&self, capacity: usize) -> usize {
+        match *self {
+            GrowthPolicy::Double => capacity,
+            GrowthPolicy::Factor(factor) => (capacity as f32 * (factor - 1.0)).max(0.0) as usize,
+            GrowthPolicy::FixedChunks(frames) => frames,
+        }
+    }
+}
+
+/// How mono sources are read into both channels.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UpmixPolicy {
+    /// Mirror the mono channel into the right channel.
+    #[default]
+    DuplicateMono,
+    /// Read the mono channel into the left channel only, the right channel stays silent.
+    LeftOnly,
+    /// Fail to open them with `SampleLoadError::UnsupportedChannelLayout`.
+    Error,
+}
+
+impl UpmixPolicy {
+    /// Upmix the mono frames written to `buffer` from `from` onwards.
+    fn apply(self, buffer: &mut StereoBuffer, from: usize) {
+        if let UpmixPolicy::LeftOnly = self {
+            let (_, right) = buffer.slice_mut(from, buffer.cursor() - from);
+            right.fill(0f32);
+        }
+    }
+}
+
+/// How sources with more channels than stereo are read.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DownmixPolicy {
+    /// Fail to open them with `SampleLoadError::UnsupportedChannelLayout`.
+    #[default]
+    Reject,
+    /// Fold them into stereo with the ITU coefficients, see `StereoBuffer::append_audio_buffer`.
+    Itu,
+}
+
+/// What a slice copied into caller provided storage is made of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SliceState {
+    /// The slice is filled with content.
+    Full,
+    /// Only the given number of frames are content, the rest is padded with silence.
+    PartiallyPadded(usize),
+    /// No content was left, the slice is filled with silence.
+    End,
+}
+
+/// Frame count statistics over decoded packets.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PacketFrameStats {
+    pub min: usize,
+    pub max: usize,
+    pub mean: f32,
+    /// Number of packets the statistics were collected over
+    pub count: usize,
+}
+
+impl PacketFrameStats {
+    fn push(&mut self, frames: usize) {
+        self.min = if self.count == 0 {
+            frames
+        } else {
+            std::cmp::min(self.min, frames)
+        };
+        self.max = std::cmp::max(self.max, frames);
+        self.count += 1;
+        self.mean += (frames as f32 - self.mean) / self.count as f32;
+    }
+}
+
+/// Sparse mapping of source timestamps to frames of the decoded content.
+///
+/// Sources without native seek tables (such as VBR MP3s) can only be seeked accurately by
+/// decoding from the start, so the mapping is recorded once while decoding instead.
+#[derive(Debug, Clone, Default)]
+pub struct SeekIndex {
+    /// Minimum number of frames between two points
+    interval: usize,
+    /// Pairs of source timestamp and decoded frame, in ascending order
+    points: Vec<(u64, usize)>,
+}
+
+impl SeekIndex {
+    fn new(interval: usize) -> Self {
+        Self {
+            interval,
+            points: Vec::new(),
+        }
+    }
+
+    /// Record a point, unless it is closer than `interval` frames to the previous one.
+    fn insert(&mut self, ts: u64, frame: usize) {
+        match self.points.last() {
+            Some((_, last)) if frame < last + self.interval => {}
+            _ => self.points.push((ts, frame)),
+        }
+    }
+
+    /// Pairs of source timestamp and decoded frame, in ascending order.
+    pub fn points(&self) -> &[(u64, usize)] {
+        &self.points
+    }
+
+    /// Frame of the decoded content at the given source timestamp, offset from the closest preceding point.
+    pub fn frame_of(&self, ts: u64) -> usize {
+        let preceding = self.points.partition_point(|(point_ts, _)| *point_ts <= ts);
+        match self.points[..preceding].last().or(self.points.first()) {
+            Some(&(point_ts, frame)) => (frame as u64 + ts).saturating_sub(point_ts) as usize,
+            None => ts as usize,
+        }
+    }
+}
+
 /// The thing that reads and decodes a sample.
 pub struct Reader {
     /// Data related to the MediaSourceStream to be decoded
     pub meta: ReaderMeta,
+    /// Tags found while opening the source, kept at the end of `meta.tags`
+    opening_tags: usize,
     /// Data structures used to decode the targeted MediaSourceStream
     track: Track,
     format: Box<dyn FormatReader>,
     decoder: Box<dyn Decoder>,
+    /// Packet read while opening the source, decoded before any other
+    pending_packet: Option<Packet>,
+    /// Frames to discard from the next decoded packets, used to land exactly on a seek target
+    skip: usize,
+    /// Signal specification of the first decoded packet
+    spec: Option<SignalSpec>,
+    /// Source timestamp of the first frame issued by the last decoded packet
+    decoded_ts: u64,
+    warnings: Vec<DecodeWarning>,
+    /// Applied to every decoded packet
+    channel_map: ChannelMap,
+    upmix_policy: UpmixPolicy,
+    /// Whether decoded samples beyond [-1, 1] are counted
+    detect_clipping: bool,
+    clipped_samples: usize,
+    /// Source timestamp of the first frame holding a clipped sample
+    first_clipped_ts: Option<u64>,
+    /// Reset the decoder and carry on when the source requires it, instead of failing
+    recover_on_reset: bool,
+    /// Whether any decoded packet had to be converted to f32 samples
+    used_conversion: bool,
+    /// Set when seeking past the end, until the next seek
+    at_end: bool,
+    /// Encoder delay trimmed by the reader in the time base of the track, source timestamps are
+    /// offset by it
+    trimmed_delay_ts: u64,
+    /// Frame of the track where the encoder padding starts, which is dropped, when the frame count
+    /// is known
+    padding_frame: Option<u64>,
 }
 
 impl Reader {
@@ -45,25 +234,135 @@ impl Reader {
         fmt_opts: FormatOptions,
         dec_opts: DecoderOptions,
     ) -> Result<Self, SampleLoadError> {
-        let (track, format, decoder, meta) =
-            prepare_sample_reader(path, meta_opts, fmt_opts, dec_opts)?;
+        Self::with_downmix_policy(path, DownmixPolicy::Reject, meta_opts, fmt_opts, dec_opts)
+    }
+
+    /// Same as `new`, reading sources with more channels than stereo according to `downmix`.
+    pub fn with_downmix_policy(
+        path: PathBuf,
+        downmix: DownmixPolicy,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let media_source = prepare_media_source(&path)?;
+        Self::from_media_source_stream(
+            media_source,
+            SourceDescriptor::File(path),
+            downmix,
+            meta_opts,
+            fmt_opts,
+            dec_opts,
+        )
+    }
+
+    /// Read a sample held in memory, without touching the filesystem.
+    ///
+    /// `hint_ext` is the extension of the file the bytes would be stored as. It only describes
+    /// the source, the container is identified by its content.
+    pub fn from_bytes(data: Vec<u8>, hint_ext: Option<&str>) -> Result<Self, SampleLoadError> {
+        let source = SourceDescriptor::Memory {
+            len: data.len(),
+            extension: hint_ext.map(str::to_owned),
+        };
+        Self::from_media_source_stream(
+            prepare_memory_source(data),
+            source,
+            DownmixPolicy::Reject,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+    }
+
+    /// Read a sample from any seekable source, such as a memory map or a decrypting wrapper.
+    ///
+    /// There is no path to infer it from, so `hint_ext` describes the source explicitly, see
+    /// `from_bytes`.
+    pub fn from_media_source(
+        source: Box<dyn MediaSource>,
+        hint_ext: Option<&str>,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        Self::from_media_source_stream(
+            MediaSourceStream::new(source, Default::default()),
+            SourceDescriptor::Custom {
+                extension: hint_ext.map(str::to_owned),
+            },
+            DownmixPolicy::Reject,
+            meta_opts,
+            fmt_opts,
+            dec_opts,
+        )
+    }
+
+    fn from_media_source_stream(
+        media_source: MediaSourceStream,
+        source: SourceDescriptor,
+        downmix: DownmixPolicy,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let (track, format, decoder, meta, pending_packet) =
+            prepare_sample_reader(media_source, source, downmix, meta_opts, fmt_opts, dec_opts)?;
+
+        // Gapless containers trim the delay and padding themselves
+        let trimmed_delay = match fmt_opts.enable_gapless {
+            true => 0,
+            false => meta.delay as u64,
+        };
+        let trimmed_delay_ts = meta.frames_to_ts(trimmed_delay) - meta.start_ts;
+        // Without padding to drop, frames past a miscounted frame count are content
+        let padding_frame = match meta.padding {
+            0 => None,
+            _ => meta.n_samples.map(|n| trimmed_delay + n),
+        };
 
         Ok(Self {
+            opening_tags: meta.tags.len(),
             meta,
             track,
             format,
             decoder,
+            pending_packet,
+            skip: trimmed_delay as usize,
+            spec: None,
+            decoded_ts: 0,
+            warnings: Vec::new(),
+            channel_map: ChannelMap::default(),
+            upmix_policy: UpmixPolicy::default(),
+            detect_clipping: false,
+            clipped_samples: 0,
+            first_clipped_ts: None,
+            recover_on_reset: false,
+            used_conversion: false,
+            at_end: false,
+            trimmed_delay_ts,
+            padding_frame,
         })
     }
 
     fn decode_next(&mut self, packet: &Packet) -> Result<AudioBufferRef<'_>, SampleDecodeError> {
         // Consume any new metadata that has been read since the last packet.
+        let mut new_revision = false;
         while !self.format.metadata().is_latest() {
             // Pop the old head of the metadata queue.
             self.format.metadata().pop();
+            new_revision = true;
+        }
+        // The new metadata at the head of the queue replaces the previous mid-stream revision, it
+        // takes precedence over the tags found while opening the source.
+        if new_revision {
+            if let Some(revision) = self.format.metadata().current() {
+                let opening = self.meta.tags.len() - self.opening_tags;
+                let mut opening_tags = self.meta.tags.split_off(opening);
+                self.meta.tags = revision.tags().to_vec();
+                self.meta.tags.append(&mut opening_tags);
+            }
         }
-        // Consume the new metadata at the head of the metadata queue.
-        // Currently there is no use for that.
 
         // If the packet does not belong to the selected track, skip over it.
         if packet.track_id() != self.track.id {
@@ -81,21 +380,49 @@ impl Reader {
                 }
             }
             Err(errors::Error::DecodeError(_)) => Err(SampleDecodeError::SkippablePacket),
-            Err(e) => Err(symph_err![e]),
+            Err(errors::Error::ResetRequired) => Err(SampleDecodeError::ResetRequired),
+            Err(e) => Err(SampleLoadError::from(e).into()),
         };
     }
 
+    /// Decode the next packet of the track into `buffer`, spilling what does not fit into `remainder`.
+    ///
+    /// Unless gapless playback is enabled through the `FormatOptions` (in which case the container
+    /// takes care of it), the encoder delay is skipped and the encoder padding is dropped, even
+    /// across packet boundaries. The padding is only known, thus dropped, if the frame count is.
+    /// Sources without padding are decoded to their end, even past a wrong frame count.
+    ///
+    /// Sources may change their format mid-stream (chained Ogg, adaptive streams). A change in the
+    /// channel count is mixed into stereo like any other packet and reported as a
+    /// `DecodeWarning::FormatChanged`. A change in the sample rate cannot be represented by the
+    /// buffer, so it fails with `SampleLoadError::UnexpectedState` before anything is appended.
     pub fn next_packet(
         &mut self,
         buffer: &mut StereoBuffer,
         remainder: &mut StereoBuffer,
     ) -> Result<ReadingProjection, SampleLoadError> {
-        let is_end: bool;
+        if self.at_end {
+            return Ok(ReadingProjection::EndReached);
+        }
+
         let already_written = buffer.cursor();
+        let remainder_written = remainder.cursor();
+        let mut skip = self.skip;
+        let mut packet_ts = 0;
+        let mut decoded = None;
+        let initial_spec = self.spec;
+        let mut used_conversion = false;
+        let recover_on_reset = self.recover_on_reset;
+        let map = self.channel_map;
 
-        loop {
-            let decoded_result = match self.format.next_packet() {
-                Ok(packet) => self.decode_next(&packet),
+        let is_end = loop {
+            let decoded_result = match self.next_container_packet() {
+                // Packets of other tracks are not worth a warning
+                Ok(packet) if packet.track_id() != self.track.id => continue,
+                Ok(packet) => {
+                    packet_ts = packet.ts();
+                    self.decode_next(&packet)
+                }
                 Err(errors::Error::IoError(e)) => {
                     if e.kind() == std::io::ErrorKind::UnexpectedEof {
                         Err(SampleDecodeError::EndReached)
@@ -103,16 +430,48 @@ impl Reader {
                         Err(SampleDecodeError::SkippablePacket)
                     }
                 }
-                Err(e) => Err(symph_err![e]),
+                Err(errors::Error::ResetRequired) => Err(SampleDecodeError::ResetRequired),
+                Err(e) => Err(SampleLoadError::from(e).into()),
             };
 
             match decoded_result {
-                Ok(raw_buf) => buffer.append_audio_buffer_ref(&raw_buf, remainder),
-                Err(SampleDecodeError::EndReached) => {
-                    is_end = true;
-                    break;
+                Ok(raw_buf) => {
+                    used_conversion = !matches!(raw_buf, AudioBufferRef::F32(_));
+                    if let Some(initial) = initial_spec {
+                        if raw_buf.spec().rate != initial.rate {
+                            return Err(SampleLoadError::UnexpectedState("format changed"));
+                        }
+                    }
+
+                    // Drop the leading frames which precede the seek target
+                    let skipped = std::cmp::min(skip, raw_buf.frames());
+                    match &raw_buf {
+                        _ if skipped == 0 => {
+                            buffer.append_audio_buffer_ref_mapped(&raw_buf, map, remainder)
+                        }
+                        // Float content is read in place, only other sample formats are converted
+                        AudioBufferRef::F32(input) => {
+                            buffer.append_audio_buffer_mapped(input, skipped, map, remainder)
+                        }
+                        _ => buffer.append_audio_buffer_mapped(
+                            &convert_any_audio_buffer(&raw_buf),
+                            skipped,
+                            map,
+                            remainder,
+                        ),
+                    }
+                    skip -= skipped;
+                    decoded = Some((*raw_buf.spec(), packet_ts, skipped as u64));
+                    break false;
                 }
+                Err(SampleDecodeError::EndReached) => break true,
                 Err(SampleDecodeError::SkippablePacket) => {
+                    self.warnings.push(DecodeWarning::SkippedPacket);
+                    continue;
+                }
+                Err(SampleDecodeError::ResetRequired) if recover_on_reset => {
+                    self.reset_decoder();
+                    self.warnings.push(DecodeWarning::DecoderReset);
                     continue;
                 }
                 Err(SampleDecodeError::ResetRequired) => {
@@ -122,6 +481,67 @@ impl Reader {
                     return Err(e);
                 }
             };
+        };
+
+        // Decoders verifying their output (DecoderOptions::verify) only know the outcome at the end
+        if is_end && self.decoder.finalize().verify_ok == Some(false) {
+            return Err(SampleLoadError::VerificationFailed);
+        }
+
+        self.skip = skip;
+        self.used_conversion |= used_conversion;
+        // Timestamp and frame of the track of the first appended frame
+        let decoded = decoded.map(|(spec, packet_ts, skipped)| {
+            let ts = packet_ts + self.frames_to_span(skipped);
+            (spec, ts, self.meta.ts_to_frames(packet_ts) + skipped)
+        });
+        // Drop the encoder padding, which follows the content
+        if let (Some((_, _, frame)), Some(padding_frame)) = (decoded, self.padding_frame) {
+            let in_remainder = remainder.cursor() - remainder_written;
+            let appended = buffer.cursor() - already_written + in_remainder;
+            let padding = (frame + appended as u64).saturating_sub(padding_frame) as usize;
+            let padding = std::cmp::min(padding, appended);
+
+            let from_remainder = std::cmp::min(padding, in_remainder);
+            remainder.resize(remainder.cursor() - from_remainder);
+            buffer.resize(buffer.cursor() - (padding - from_remainder));
+        }
+
+        if let Layout::Mono = self.meta.layout {
+            self.upmix_policy.apply(buffer, already_written);
+            self.upmix_policy.apply(remainder, remainder_written);
+        }
+        self.channel_map.apply(buffer, already_written);
+        self.channel_map.apply(remainder, remainder_written);
+
+        // Counted as read, mono sources count once per channel they are read into
+        if let (true, Some((_, _, first_frame))) = (self.detect_clipping, decoded) {
+            let written = buffer.cursor() - already_written;
+            let (left, right) = buffer.slice(already_written, written);
+            let (clipped, first) = count_clipped(left, right);
+            let (left, right) =
+                remainder.slice(remainder_written, remainder.cursor() - remainder_written);
+            let (clipped_remainder, first_remainder) = count_clipped(left, right);
+
+            self.clipped_samples += clipped + clipped_remainder;
+            if self.first_clipped_ts.is_none() {
+                let first = first.or(first_remainder.map(|frame| written + frame));
+                self.first_clipped_ts = first.map(|frame| {
+                    let ts = self.meta.frames_to_ts(first_frame + frame as u64);
+                    ts.saturating_sub(self.trimmed_delay_ts)
+                });
+            }
+        }
+
+        if let Some((spec, ts, _)) = decoded {
+            self.decoded_ts = ts.saturating_sub(self.trimmed_delay_ts);
+            match self.spec {
+                None => self.spec = Some(spec),
+                Some(initial) if initial != spec => {
+                    self.warnings.push(DecodeWarning::FormatChanged(spec))
+                }
+                Some(_) => {}
+            }
         }
 
         Ok(if is_end {
@@ -131,11 +551,390 @@ impl Reader {
         })
     }
 
+    /// Read mono sources into both channels according to `policy`.
+    ///
+    /// Fails with `SampleLoadError::UnsupportedChannelLayout` when the source is mono and the
+    /// policy is `UpmixPolicy::Error`.
+    fn set_upmix_policy(&mut self, policy: UpmixPolicy) -> Result<(), SampleLoadError> {
+        if let (Layout::Mono, UpmixPolicy::Error) = (self.meta.layout, policy) {
+            return Err(SampleLoadError::UnsupportedChannelLayout(Layout::Mono));
+        }
+        self.upmix_policy = policy;
+        Ok(())
+    }
+
+    /// Whether `seek` is supported by the source, see `ReaderMeta::is_seekable`.
+    pub fn is_seekable(&self) -> bool {
+        self.meta.is_seekable()
+    }
+
+    /// Whether the source states its frame count, so its duration can be trusted.
+    pub fn duration_is_known(&self) -> bool {
+        self.meta.n_samples.is_some()
+    }
+
+    /// Fail with `SampleLoadError::SampleRateMismatch` unless the source is sampled at `expected`.
+    ///
+    /// This is the rate of the source, before any resampling by the readers built on top of it.
+    pub fn expect_sample_rate(&self, expected: u32) -> Result<(), SampleLoadError> {
+        match self.meta.sample_rate {
+            actual if actual != expected => {
+                Err(SampleLoadError::SampleRateMismatch { expected, actual })
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Title, artist and album of the sample, keyed by their lowercase names.
+    ///
+    /// Tags found in the latest metadata revisions take precedence, missing ones are left out.
+    pub fn tags(&self) -> Vec<(String, String)> {
+        [
+            ("title", StandardTagKey::TrackTitle),
+            ("artist", StandardTagKey::Artist),
+            ("album", StandardTagKey::Album),
+        ]
+        .into_iter()
+        .filter_map(|(name, key)| {
+            self.meta
+                .tag(key)
+                .map(|value| (name.to_owned(), value.to_owned()))
+        })
+        .collect()
+    }
+
+    /// Source timestamp of the first frame issued by the most recently decoded packet.
+    ///
+    /// Like every timestamp issued by the reader, it does not account for the trimmed encoder delay.
+    ///
+    /// This tracks decoding, which usually runs ahead of playback. Readers issuing the decoded
+    /// content keep their own playback cursor.
+    pub fn decoded_position(&self) -> u64 {
+        self.decoded_ts
+    }
+
+    /// Channel layout of the source, available as soon as the reader is opened.
+    pub fn layout(&self) -> Layout {
+        self.meta.layout
+    }
+
+    /// Number of channels of the source, available as soon as the reader is opened.
+    ///
+    /// Issued buffers are always stereo, this is the count before any upmix or downmix.
+    pub fn channel_count(&self) -> usize {
+        self.meta.layout.into_channels().count()
+    }
+
+    /// Whether any packet decoded so far had a sample format other than f32, which required a conversion.
+    pub fn used_conversion(&self) -> bool {
+        self.used_conversion
+    }
+
+    /// Reset the decoder and carry on with the next packet when the source requires a reset,
+    /// which is routine for live streams and chained Ogg files. Defaults to `false`, failing with
+    /// `SampleLoadError::ResetRequired`.
+    ///
+    /// Each reset is reported as a `DecodeWarning::DecoderReset`. Frames around the reset point
+    /// may be lost.
+    pub fn recover_on_reset(&mut self, recover: bool) {
+        self.recover_on_reset = recover;
+    }
+
+    /// Count the decoded samples beyond [-1, 1], typically intersample peaks of lossy sources. Defaults to `false`.
+    pub fn detect_clipping(&mut self, detect: bool) {
+        self.detect_clipping = detect;
+    }
+
+    /// Samples of both channels decoded beyond [-1, 1] so far, while `detect_clipping` was enabled.
+    ///
+    /// Samples are counted as read, after the upmix: mono sources are counted once per channel
+    /// they are read into, a silent right channel is not counted.
+    pub fn clipped_sample_count(&self) -> usize {
+        self.clipped_samples
+    }
+
+    /// Source timestamp of the first frame decoded with a clipped sample, see `clipped_sample_count`.
+    pub fn first_clipped_position(&self) -> Option<u64> {
+        self.first_clipped_ts
+    }
+
+    /// Recoverable issues encountered so far while decoding.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        &self.warnings
+    }
+
+    /// Seek to the given position, leaving the reader ready to decode from there.
+    ///
+    /// The decoder is reset, so the same reader can be seeked again without being reconstructed.
+    /// Frames between the seeked packet and the requested position are discarded by `next_packet`,
+    /// so the first frame it issues is the one at `position`.
+    ///
+    /// Positions past the end are clamped to the end, after which `next_packet` reports
+    /// `EndReached` until the reader is seeked again.
+    ///
+    /// Returns the index of the frame of the content landed on, the first one the next
+    /// `next_packet` call will issue.
+    pub fn seek(&mut self, position: Duration) -> Result<u64, SampleLoadError> {
+        self.seek_ts(self.duration_to_ts(position))
+    }
+
+    /// Timestamp in the time base of the track of a position in the content.
+    fn duration_to_ts(&self, position: Duration) -> u64 {
+        // Rounded rather than truncated, durations are not exact enough to land on a frame
+        let time_base = self.meta.time_base;
+        let ts = position.as_secs_f64() * time_base.denom as f64 / time_base.numer as f64;
+        ts.round() as u64
+    }
+
+    /// Timestamp right past the last frame of the track, if the frame count is known.
+    fn end_ts(&self) -> Option<u64> {
+        self.meta.n_samples.map(|n| self.meta.frames_to_ts(n))
+    }
+
+    /// Length in the time base of the track of a span of `frames`.
+    fn frames_to_span(&self, frames: u64) -> u64 {
+        self.meta.frames_to_ts(frames) - self.meta.start_ts
+    }
+
+    /// Frames spanned by `span`, a length in the time base of the track.
+    fn span_to_frames(&self, span: u64) -> u64 {
+        self.meta.ts_to_frames(self.meta.start_ts + span)
+    }
+
+    /// Same as `seek`, expressed as a symphonia `Time`.
+    fn seek_time(&mut self, time: Time) -> Result<u64, SampleLoadError> {
+        self.seek_ts(self.meta.time_base.calc_timestamp(time))
+    }
+
+    /// Same as `seek`, expressed as a timestamp in the time base of the track.
+    fn seek_ts(&mut self, ts: u64) -> Result<u64, SampleLoadError> {
+        match self.seek_container(ts)? {
+            Some(seeked_to) => {
+                let skip = seeked_to.required_ts.saturating_sub(seeked_to.actual_ts);
+                self.skip = self.span_to_frames(skip) as usize;
+                let ts = seeked_to.required_ts.saturating_sub(self.trimmed_delay_ts);
+                Ok(self.span_to_frames(ts))
+            }
+            None => {
+                let frame = self.span_to_frames(ts);
+                Ok(self
+                    .meta
+                    .n_samples
+                    .map_or(frame, |n| std::cmp::min(frame, n)))
+            }
+        }
+    }
+
+    /// Seek the container to the packet holding `ts`, reporting source timestamps, which are
+    /// offset by the trimmed delay.
+    ///
+    /// Returns `None` once past the end, after which `next_packet` reports `EndReached`.
+    fn seek_container(&mut self, ts: u64) -> Result<Option<SeekedTo>, SampleLoadError> {
+        // The container is read again from the seeked position
+        self.pending_packet = None;
+        if self.end_ts().is_some_and(|end_ts| ts >= end_ts) {
+            self.at_end = true;
+            self.skip = 0;
+            return Ok(None);
+        }
+
+        let seeked_to = match self.format.seek(
+            SeekMode::Accurate,
+            SeekTo::TimeStamp {
+                ts: ts + self.trimmed_delay_ts,
+                track_id: self.track.id,
+            },
+        ) {
+            Ok(seeked_to) => seeked_to,
+            // Sources of unknown length only find out while seeking
+            Err(errors::Error::SeekError(errors::SeekErrorKind::OutOfRange)) => {
+                self.at_end = true;
+                self.skip = 0;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        self.reset_decoder();
+        self.at_end = false;
+        Ok(Some(seeked_to))
+    }
+
+    /// Seek to the given position, warming the decoder up with the `packets` packets preceding it.
+    ///
+    /// Lossy decoders (MP3, AAC) need some context before their output is accurate, which a plain
+    /// `seek` discards. The seek lands `packets` packets earlier instead, and the frames up to the
+    /// requested position are decoded and discarded by `next_packet`, just like they are after a
+    /// `seek`.
+    ///
+    /// Behaves like `seek` when the packet size of the source is unknown, and returns the frame
+    /// landed on just like it.
+    pub fn seek_accurate(
+        &mut self,
+        position: Duration,
+        packets: usize,
+    ) -> Result<u64, SampleLoadError> {
+        let preroll_frames = packets as u64 * self.meta.max_samples_per_packet.unwrap_or(0);
+        let preroll = Duration::from_secs_f64(preroll_frames as f64 / self.meta.sample_rate as f64);
+
+        let required_ts = self.duration_to_ts(position);
+        if self.end_ts().is_some_and(|end_ts| required_ts >= end_ts) {
+            return self.seek(position);
+        }
+
+        let preroll_ts = self.duration_to_ts(position.saturating_sub(preroll));
+        let seeked_to = match self.seek_container(preroll_ts)? {
+            Some(seeked_to) => seeked_to,
+            None => return self.seek(position),
+        };
+
+        // Landing within the trimmed delay, the frames up to the content are discarded as well
+        let required_ts = std::cmp::max(required_ts + self.trimmed_delay_ts, seeked_to.actual_ts);
+        let skip = required_ts - seeked_to.actual_ts;
+        self.skip = self.span_to_frames(skip) as usize;
+
+        Ok(self.span_to_frames(required_ts.saturating_sub(self.trimmed_delay_ts)))
+    }
+
+    /// Decode and discard up to `packets` packets, warming the decoder up.
+    ///
+    /// The content of the discarded packets is lost, use `seek_accurate` to warm the decoder up
+    /// before a given time instead.
+    ///
+    /// Returns the number of discarded frames per channel, which may span fewer packets if the
+    /// end is reached.
+    pub fn preroll(&mut self, packets: usize) -> Result<usize, SampleLoadError> {
+        let mut frames = 0;
+        let mut decoded = 0;
+
+        while decoded < packets {
+            let decoded_result = match self.next_container_packet() {
+                Ok(packet) if packet.track_id() != self.track.id => continue,
+                Ok(packet) => self.decode_next(&packet).map(|raw_buf| raw_buf.frames()),
+                Err(errors::Error::IoError(e)) => {
+                    if e.kind() == std::io::ErrorKind::UnexpectedEof {
+                        Err(SampleDecodeError::EndReached)
+                    } else {
+                        Err(SampleDecodeError::SkippablePacket)
+                    }
+                }
+                Err(errors::Error::ResetRequired) => Err(SampleDecodeError::ResetRequired),
+                Err(e) => Err(SampleLoadError::from(e).into()),
+            };
+
+            match decoded_result {
+                Ok(packet_frames) => frames += packet_frames,
+                Err(SampleDecodeError::EndReached) => break,
+                Err(SampleDecodeError::SkippablePacket) => {
+                    self.warnings.push(DecodeWarning::SkippedPacket)
+                }
+                Err(SampleDecodeError::ResetRequired) if self.recover_on_reset => {
+                    self.reset_decoder();
+                    self.warnings.push(DecodeWarning::DecoderReset);
+                }
+                Err(SampleDecodeError::ResetRequired) => {
+                    return Err(SampleLoadError::ResetRequired);
+                }
+                Err(SampleDecodeError::LoadError(e)) => {
+                    return Err(e);
+                }
+            }
+            decoded += 1;
+        }
+
+        // Frames pending to be skipped were discarded along with the packets
+        self.skip = self.skip.saturating_sub(frames);
+
+        Ok(frames)
+    }
+
+    /// Decode the whole content, keeping only the minimum and maximum of both channels over
+    /// `buckets` spans of equal duration, such as the columns of a waveform thumbnail.
+    ///
+    /// Spans are computed from the stated frame count. Frames past it are kept in the last
+    /// bucket, and buckets past the end of a shorter content are `(0.0, 0.0)`. The reader is left
+    /// at the end of the content.
+    ///
+    /// Fails with `SampleLoadError::MissingRequiredMetadata` when the source does not state its
+    /// frame count.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buckets` is 0.
+    pub fn compute_peaks(&mut self, buckets: usize) -> Result<Vec<(f32, f32)>, SampleLoadError> {
+        assert!(buckets > 0, "at least one bucket is required");
+        let total = self
+            .meta
+            .n_samples
+            .ok_or(SampleLoadError::MissingRequiredMetadata("frame count"))?
+            .max(1);
+
+        let packet_len = self.meta.max_samples_per_packet.unwrap_or(4096) as usize;
+        let mut packet = StereoBuffer::new(packet_len);
+        let mut packet_overflow = StereoBuffer::new(packet_len);
+        let mut peaks = vec![(f32::INFINITY, f32::NEG_INFINITY); buckets];
+        let mut frame = 0u64;
+
+        loop {
+            packet.clear_cursor();
+            packet_overflow.clear_cursor();
+
+            let size = match self.next_packet(&mut packet, &mut packet_overflow)? {
+                ReadingProjection::EndReached => break,
+                ReadingProjection::SamplesRead(size) => size,
+            };
+            for written in [&packet, &packet_overflow] {
+                let (left, right) = written.slice(0, written.cursor());
+                for (l, r) in left.iter().zip(right) {
+                    let bucket = (frame * buckets as u64 / total) as usize;
+                    let (min, max) = &mut peaks[std::cmp::min(bucket, buckets - 1)];
+                    *min = min.min(*l).min(*r);
+                    *max = max.max(*l).max(*r);
+                    frame += 1;
+                }
+            }
+
+            // Make room for bigger packets than anticipated
+            let capacity = packet.channel_capacity();
+            if size > capacity {
+                packet.reserve_exact(size - capacity);
+                packet_overflow.reserve_exact(size - capacity);
+            }
+        }
+
+        Ok(peaks
+            .into_iter()
+            .map(|(min, max)| if min > max { (0.0, 0.0) } else { (min, max) })
+            .collect())
+    }
+
+    /// The next packet of the container, starting with the pending one.
+    fn next_container_packet(&mut self) -> errors::Result<Packet> {
+        match self.pending_packet.take() {
+            Some(packet) => Ok(packet),
+            None => self.format.next_packet(),
+        }
+    }
+
     fn reset_decoder(&mut self) {
         self.decoder.reset()
     }
 }
 
+/// Count the samples of both channels beyond [-1, 1], along with the first frame holding one.
+fn count_clipped(left: &[f32], right: &[f32]) -> (usize, Option<usize>) {
+    let mut clipped = 0;
+    let mut first = None;
+    for (frame, (l, r)) in left.iter().zip(right).enumerate() {
+        let frame_clipped = (l.abs() > 1.0) as usize + (r.abs() > 1.0) as usize;
+        if frame_clipped > 0 && first.is_none() {
+            first = Some(frame);
+        }
+        clipped += frame_clipped;
+    }
+    (clipped, first)
+}
+
 /// Describes the reading capabilities of a sample reader
 ///
 /// - Can read a buffer worth of content
@@ -151,21 +950,75 @@ pub trait SampleReader {
     fn read_sync(&mut self) -> Result<(), SampleLoadError>;
 
     /// Issue the next slice of samples for both channels
-    fn next_slice(&mut self) -> (&[f32], &[f32]);
+    ///
+    /// Fails with `SampleLoadError::BufferError` instead of panicking when the slice would run past
+    /// the internal buffer.
+    fn next_slice(&mut self) -> Result<(&[f32], &[f32]), SampleLoadError>;
 
-    /// Get the next sample from the requested channel
-    // fn next_sample(&mut self) -> Result<f32, SampleLoadError>;
+    /// Get the next sample from the requested channel.
+    ///
+    /// Each channel has its own sample cursor, independent of the slices issued by `next_slice`.
+    /// Readers which cannot issue single samples report `SampleLoadError::UnexpectedState`.
+    fn next_sample(&mut self, _channel: Channel) -> Result<f32, SampleLoadError> {
+        Err(SampleLoadError::UnexpectedState(
+            "single samples are not supported by this reader",
+        ))
+    }
 
     /// A number between 0 and 1 indicating the percentage of the internal buffer that has been consumed
     ///
     /// This value can be used to determine when to issue a new buffer read.
+    /// Empty buffers are fully consumed, so this is never NaN.
+    fn percentage_consumed(&self) -> f32;
+
+    /// Frames per channel left in the internal buffer before issuing wraps around or refills it.
+    ///
+    /// Unlike `percentage_consumed`, this tells whether the next slice is the last one before then.
+    fn remaining_samples(&self) -> usize;
+
+    /// Restart issuing from the beginning of the content.
+    ///
+    /// Readers which cannot go back do nothing, which is the default.
+    fn rewind(&mut self) {}
+
+    /// Whether at least `threshold` (between 0 and 1) of the internal buffer has been consumed.
+    fn needs_refill(&self, threshold: f32) -> bool {
+        self.percentage_consumed() >= threshold
+    }
+
+    /// Read the next buffer worth of content, once `needs_refill` holds.
+    ///
+    /// Readers holding their whole content in memory have nothing to refill, which is the default.
+    fn refill(&mut self) -> Result<(), SampleLoadError> {
+        Ok(())
+    }
+}
+
+/// Same as `SampleReader`, for readers issuing a single channel.
+pub trait MonoSampleReader {
+    /// Read a buffer worth of content
+    fn read_sync(&mut self) -> Result<(), SampleLoadError>;
+
+    /// Issue the next slice of samples, see `SampleReader::next_slice`
+    fn next_slice(&mut self) -> Result<&[f32], SampleLoadError>;
+
+    /// A number between 0 and 1 indicating the percentage of the internal buffer that has been consumed
     fn percentage_consumed(&self) -> f32;
 }
 
 pub mod prelude {
+    #[cfg(feature = "tokio")]
+    pub use super::async_reader::{AsyncSampleReader, AsyncStreamReader};
     pub use super::{
-        error::{SampleDecodeError, SampleLoadError},
-        full_reader::SyncFullReader,
-        Reader, ReadingProjection, SampleReader,
+        error::{DecodeWarning, SampleDecodeError, SampleLoadError},
+        full_reader::{SyncFullReader, SyncFullReaderBuilder},
+        lookahead_reader::LookaheadReader,
+        mono_full_reader::SyncMonoFullReader,
+        prepare::SourceDescriptor,
+        scrub_reader::ScrubReader,
+        stream_reader::{SyncStreamReader, SyncStreamReaderBuilder},
+        ChannelMap, DownmixPolicy, GrowthPolicy, MonoSampleReader, PacketFrameStats,
+        PartialSlicePolicy, Reader, ReadingProjection, SampleReader, SeekIndex, SliceState,
+        UpmixPolicy,
     };
 }
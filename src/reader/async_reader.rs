@@ -0,0 +1,208 @@
+use std::future::Future;
+use symphonia::core::{
+    codecs::DecoderOptions, formats::FormatOptions, io::MediaSource, meta::MetadataOptions,
+};
+use tokio::{
+    sync::mpsc::{self, Receiver, Sender},
+    task,
+};
+
+use super::{error::SampleLoadError, Reader, ReadingProjection};
+use crate::buffer::{stereo::StereoBuffer, Buffer};
+
+/// Decoded packets queued between the decoding task and the reader before decoding stalls
+const DEFAULT_QUEUED_PACKETS: usize = 8;
+
+/// Frames of both channels decoded from a single packet
+type DecodedPacket = Result<(Vec<f32>, Vec<f32>), SampleLoadError>;
+
+/// Counterpart of `SampleReader` for readers which must not block the calling task.
+pub trait AsyncSampleReader {
+    /// Read a buffer worth of content
+    fn read_sync(&mut self) -> impl Future<Output = Result<(), SampleLoadError>> + Send;
+
+    /// Issue the next slice of samples for both channels, see `SampleReader::next_slice`
+    fn next_slice(
+        &mut self,
+    ) -> impl Future<Output = Result<(&[f32], &[f32]), SampleLoadError>> + Send;
+}
+
+/// A streaming reader which decodes on the blocking pool of tokio, for sources such as sockets.
+///
+/// Decoded packets are handed over through a bounded queue. Once it is full the decoding task
+/// stalls until slices are issued, so memory use does not grow with the source.
+pub struct AsyncStreamReader {
+    receiver: Receiver<DecodedPacket>,
+    /// The most recently issued slice
+    slice: StereoBuffer,
+    host_buffer_len: usize,
+    /// Frames received past the most recently issued slice
+    pending_left: Vec<f32>,
+    pending_right: Vec<f32>,
+    sample_rate: u32,
+    end_reached: bool,
+    /// Error which interrupted decoding, not reported yet
+    error: Option<SampleLoadError>,
+}
+
+impl AsyncStreamReader {
+    /// Probe `source` and start decoding it in the background.
+    ///
+    /// Non seekable sources, such as sockets, can be wrapped in a symphonia `ReadOnlySource`.
+    /// See `Reader::from_media_source` for `hint_ext`.
+    pub async fn new(
+        source: Box<dyn MediaSource>,
+        hint_ext: Option<&str>,
+        host_buffer_len: usize,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        Self::with_queued_packets(
+            source,
+            hint_ext,
+            host_buffer_len,
+            DEFAULT_QUEUED_PACKETS,
+            meta_opts,
+            fmt_opts,
+            dec_opts,
+        )
+        .await
+    }
+
+    /// Same as `new`, with up to `queued_packets` decoded packets waiting to be issued.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `queued_packets` is 0.
+    pub async fn with_queued_packets(
+        source: Box<dyn MediaSource>,
+        hint_ext: Option<&str>,
+        host_buffer_len: usize,
+        queued_packets: usize,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        // Probing reads from the source as well
+        let hint_ext = hint_ext.map(str::to_owned);
+        let reader = task::spawn_blocking(move || {
+            Reader::from_media_source(source, hint_ext.as_deref(), meta_opts, fmt_opts, dec_opts)
+        })
+        .await
+        .map_err(|_| SampleLoadError::UnexpectedState("the probing task panicked"))??;
+        let sample_rate = reader.meta.sample_rate;
+
+        let (sender, receiver) = mpsc::channel(queued_packets);
+        task::spawn_blocking(move || decode(reader, sender));
+
+        Ok(Self {
+            receiver,
+            slice: StereoBuffer::new(host_buffer_len),
+            host_buffer_len,
+            pending_left: Vec::new(),
+            pending_right: Vec::new(),
+            sample_rate,
+            end_reached: false,
+            error: None,
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Whether every decoded frame was issued.
+    pub fn is_finished(&self) -> bool {
+        self.end_reached && self.pending_left.is_empty()
+    }
+
+    /// Receive decoded packets until a host buffer is pending, or the end is reached.
+    async fn fill(&mut self) {
+        while self.pending_left.len() < self.host_buffer_len && !self.end_reached {
+            match self.receiver.recv().await {
+                Some(Ok((left, right))) => {
+                    self.pending_left.extend(left);
+                    self.pending_right.extend(right);
+                }
+                Some(Err(e)) => {
+                    self.error = Some(e);
+                    self.end_reached = true;
+                }
+                None => self.end_reached = true,
+            }
+        }
+    }
+}
+
+impl AsyncSampleReader for AsyncStreamReader {
+    /// Wait for the first host buffer to be decoded.
+    async fn read_sync(&mut self) -> Result<(), SampleLoadError> {
+        self.fill().await;
+        match self.error.take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+
+    /// Issue the next host buffer, padded with silence once the content runs out.
+    ///
+    /// A decoding error is returned once, the frames decoded before it are issued afterwards.
+    async fn next_slice(&mut self) -> Result<(&[f32], &[f32]), SampleLoadError> {
+        self.fill().await;
+        if let Some(e) = self.error.take() {
+            return Err(e);
+        }
+
+        let frames = std::cmp::min(self.host_buffer_len, self.pending_left.len());
+        self.slice.clear_cursor();
+        self.slice
+            .append_slices(&self.pending_left[..frames], &self.pending_right[..frames]);
+        self.slice.pad_silence();
+        self.pending_left.drain(..frames);
+        self.pending_right.drain(..frames);
+
+        Ok(self.slice.try_slice(0, self.host_buffer_len)?)
+    }
+}
+
+/// Decode every packet of `reader` into `sender`, blocking while the queue is full.
+///
+/// Stops at the end of the content, after the first error, or once the receiver is dropped.
+fn decode(mut reader: Reader, sender: Sender<DecodedPacket>) {
+    let packet_len = reader.meta.max_samples_per_packet.unwrap_or(4096) as usize;
+    let mut packet = StereoBuffer::new(packet_len);
+    let mut packet_overflow = StereoBuffer::new(packet_len);
+
+    loop {
+        packet.clear_cursor();
+        packet_overflow.clear_cursor();
+
+        let decoded = match reader.next_packet(&mut packet, &mut packet_overflow) {
+            Ok(ReadingProjection::EndReached) => return,
+            Ok(ReadingProjection::SamplesRead(size)) => {
+                let (packet_left, packet_right) = packet.slice(0, packet.cursor());
+                let (overflow_left, overflow_right) =
+                    packet_overflow.slice(0, packet_overflow.cursor());
+                let decoded = Ok((
+                    [packet_left, overflow_left].concat(),
+                    [packet_right, overflow_right].concat(),
+                ));
+
+                // Make room for bigger packets than anticipated
+                let capacity = packet.channel_capacity();
+                if size > capacity {
+                    packet.reserve_exact(size - capacity);
+                    packet_overflow.reserve_exact(size - capacity);
+                }
+                decoded
+            }
+            Err(e) => Err(e),
+        };
+
+        let failed = decoded.is_err();
+        if sender.blocking_send(decoded).is_err() || failed {
+            return;
+        }
+    }
+}
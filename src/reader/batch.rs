@@ -0,0 +1,33 @@
+use rayon::prelude::*;
+use std::path::PathBuf;
+use symphonia::core::{codecs::DecoderOptions, formats::FormatOptions, meta::MetadataOptions};
+
+use super::{error::SampleLoadError, full_reader::SyncFullReader, SampleReader, UpmixPolicy};
+
+/// Open and fully read every file of `paths` in parallel, on the global rayon thread pool.
+///
+/// Results are in the order of `paths`. Files are independent of each other, one failing to
+/// open or decode only leaves an `Err` in its own slot.
+pub fn load_many(
+    paths: Vec<PathBuf>,
+    host_buffer_len: usize,
+    meta_opts: MetadataOptions,
+    fmt_opts: FormatOptions,
+    dec_opts: DecoderOptions,
+) -> Vec<Result<SyncFullReader, SampleLoadError>> {
+    paths
+        .into_par_iter()
+        .map(|path| {
+            let mut reader = SyncFullReader::new(
+                path,
+                host_buffer_len,
+                UpmixPolicy::default(),
+                meta_opts,
+                fmt_opts,
+                dec_opts,
+            )?;
+            reader.read_sync()?;
+            Ok(reader)
+        })
+        .collect()
+}
@@ -1,6 +1,10 @@
 use std::{error::Error, fmt};
 
-use symphonia::core::{audio::Layout, errors::Error as SymphoniaError};
+use crate::buffer::error::BufferError;
+use symphonia::core::{
+    audio::{Layout, SignalSpec},
+    errors::Error as SymphoniaError,
+};
 
 #[derive(Debug)]
 pub enum SampleLoadError {
@@ -14,13 +18,27 @@ pub enum SampleLoadError {
     MissingRequiredMetadata(&'static str),
     UnexpectedState(&'static str),
     ResetRequired,
+    // Sample rate of the source differs from the one the caller expected
+    SampleRateMismatch {
+        expected: u32,
+        actual: u32,
+    },
+    // Decoded content does not match the checksum of the source
+    VerificationFailed,
+    // A slice was requested past the internal buffer
+    BufferError(BufferError),
+    // Unhandled error while writing a WAV file
+    #[cfg(feature = "hound")]
+    WavWriteError(hound::Error),
 }
 
 impl fmt::Display for SampleLoadError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            SampleLoadError::IoError(e) => write!(f, "{}", e),
-            SampleLoadError::SymphoniaError(e) => write!(f, "{}", e),
+            SampleLoadError::IoError(_) => write!(f, "Failed to read the media source"),
+            SampleLoadError::SymphoniaError(_) => {
+                write!(f, "Failed to demux or decode the media source")
+            }
             SampleLoadError::NoSupportedAudioTracks => write!(f, "No supported audio tracks found"),
             SampleLoadError::UnsupportedCodec => write!(f, "Unsupported codec"),
             SampleLoadError::UnsupportedChannelLayout(layout) => {
@@ -31,11 +49,53 @@ impl fmt::Display for SampleLoadError {
             }
             SampleLoadError::UnexpectedState(msg) => write!(f, "Unexpected read state: {}", msg),
             SampleLoadError::ResetRequired => write!(f, "{}", SymphoniaError::ResetRequired),
+            SampleLoadError::SampleRateMismatch { expected, actual } => {
+                write!(
+                    f,
+                    "Expected a sample rate of {}Hz, found {}Hz",
+                    expected, actual
+                )
+            }
+            SampleLoadError::VerificationFailed => {
+                write!(f, "Decoded content does not match its checksum")
+            }
+            SampleLoadError::BufferError(_) => write!(f, "Failed to slice the internal buffer"),
+            #[cfg(feature = "hound")]
+            SampleLoadError::WavWriteError(_) => write!(f, "Failed to write the wav file"),
+        }
+    }
+}
+
+impl Error for SampleLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SampleLoadError::IoError(e) => Some(e),
+            SampleLoadError::SymphoniaError(e) => Some(e),
+            SampleLoadError::BufferError(e) => Some(e),
+            #[cfg(feature = "hound")]
+            SampleLoadError::WavWriteError(e) => Some(e),
+            _ => None,
         }
     }
 }
 
-impl Error for SampleLoadError {}
+impl From<std::io::Error> for SampleLoadError {
+    fn from(e: std::io::Error) -> Self {
+        SampleLoadError::IoError(e)
+    }
+}
+
+impl From<BufferError> for SampleLoadError {
+    fn from(e: BufferError) -> Self {
+        SampleLoadError::BufferError(e)
+    }
+}
+
+impl From<SymphoniaError> for SampleLoadError {
+    fn from(e: SymphoniaError) -> Self {
+        SampleLoadError::SymphoniaError(e)
+    }
+}
 
 /// Errors used mostly for inner logic and reasoning
 #[derive(Debug)]
@@ -49,7 +109,7 @@ pub enum SampleDecodeError {
 impl fmt::Display for SampleDecodeError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            SampleDecodeError::LoadError(e) => write!(f, "{}", e),
+            SampleDecodeError::LoadError(_) => write!(f, "failed to load the sample"),
             SampleDecodeError::SkippablePacket => {
                 write!(f, "{}", SymphoniaError::DecodeError("skippable packet"))
             }
@@ -59,4 +119,45 @@ impl fmt::Display for SampleDecodeError {
     }
 }
 
-impl Error for SampleDecodeError {}
+impl Error for SampleDecodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SampleDecodeError::LoadError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<SampleLoadError> for SampleDecodeError {
+    fn from(e: SampleLoadError) -> Self {
+        SampleDecodeError::LoadError(e)
+    }
+}
+
+/// Recoverable issues encountered while decoding.
+///
+/// These do not interrupt reading, but are collected so they can be inspected afterwards.
+#[derive(Debug, Clone)]
+pub enum DecodeWarning {
+    /// A packet could not be decoded and was skipped
+    SkippedPacket,
+    /// The decoded channel count changed mid-stream, the content is still mixed into stereo
+    FormatChanged(SignalSpec),
+    /// The source required a reset, the decoder was reset and decoding carried on
+    DecoderReset,
+}
+
+impl fmt::Display for DecodeWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeWarning::SkippedPacket => write!(f, "skipped undecodable packet"),
+            DecodeWarning::FormatChanged(spec) => write!(
+                f,
+                "format changed to {} channels at {}Hz",
+                spec.channels.count(),
+                spec.rate
+            ),
+            DecodeWarning::DecoderReset => write!(f, "decoder reset mid-stream"),
+        }
+    }
+}
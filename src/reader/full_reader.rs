@@ -1,8 +1,19 @@
-use std::{mem::size_of, path::PathBuf};
-use symphonia::core::{codecs::DecoderOptions, formats::FormatOptions, meta::MetadataOptions};
+use std::{path::PathBuf, time::Duration};
+use symphonia::core::{
+    audio::Layout, codecs::DecoderOptions, formats::FormatOptions, io::MediaSource,
+    meta::MetadataOptions, units::Time,
+};
 
-use super::{error::SampleLoadError, Reader, ReadingProjection, SampleReader};
-use crate::buffer::{stereo::StereoBuffer, Buffer};
+use super::{
+    error::{DecodeWarning, SampleLoadError},
+    ChannelMap, DownmixPolicy, GrowthPolicy, PacketFrameStats, PartialSlicePolicy, Reader,
+    ReadingProjection, SampleReader, SeekIndex, SliceState, UpmixPolicy,
+};
+use crate::buffer::{
+    resample::{ResampleQuality, Resampler},
+    stereo::{Channel, StereoBuffer},
+    Buffer,
+};
 
 /// A reader which loads the full content of a sample into memory.
 ///
@@ -10,51 +21,327 @@ use crate::buffer::{stereo::StereoBuffer, Buffer};
 ///
 /// Sample issuing will eventually round robin.
 ///
-/// The total capacity will be a multiple of the host buffer length.
+/// The total capacity will be a multiple of the host buffer length, unless alignment is disabled
+/// through the `SyncFullReaderBuilder`.
 pub struct SyncFullReader {
     pub buffer: StereoBuffer,
+    /// Channel layout of the source, the buffer is always stereo
+    pub source_layout: Layout,
     reader: Reader,
     /// Reading cursor, not to be confused with the buffer cursor used for writing
     cursor: usize,
+    /// Reading cursors of `next_sample`, one per channel
+    sample_cursors: [usize; 2],
     host_buffer_len: usize,
+    /// Frames of decoded content, excluding the silence padded for alignment
+    content_len: usize,
+    /// Frames of the crossfaded loop, followed by its start, see `set_loop_crossfade`
+    loop_len: Option<usize>,
+    /// Align the buffer to the host buffer length and pad it with silence after reading
+    align: bool,
+    partial_slice_policy: PartialSlicePolicy,
+    /// Growth of the buffer while reading sources of unknown length
+    growth_policy: GrowthPolicy,
+    /// Collect per packet frame counts while reading
+    collect_packet_stats: bool,
+    packet_frame_stats: PacketFrameStats,
+    /// Sum of squares of all decoded samples, over both channels
+    sum_of_squares: f64,
+    decoded_samples: usize,
+    /// Record the source timestamp of each decoded packet while reading
+    index_timestamps: bool,
+    timestamp_index: Vec<(usize, u64)>,
+    /// Only built while reading when enabled through the `SyncFullReaderBuilder`
+    seek_index: Option<SeekIndex>,
+    /// Frames to read from the reader position, see `new_range`
+    range_frames: Option<usize>,
+    /// Resamples the content once read, see `new_resampled`
+    resampler: Option<Resampler>,
+    /// Sample rate of the buffer
+    sample_rate: u32,
 }
 
+/// Seconds of content between two points of the seek index
+const SEEK_INDEX_INTERVAL: f32 = 0.1;
+
 impl SyncFullReader {
+    /// Open the sample at `path`, reading mono sources into both channels according to `upmix`.
     pub fn new(
         path: PathBuf,
         host_buffer_len: usize,
+        upmix: UpmixPolicy,
         meta_opts: MetadataOptions,
         fmt_opts: FormatOptions,
         dec_opts: DecoderOptions,
     ) -> Result<Self, SampleLoadError> {
-        let reader = Reader::new(path, meta_opts, fmt_opts, dec_opts)?;
-        // exact sample count or 1MB worth of samples for 2 f32 channels
-        let estimated_size = reader
-            .meta
-            .n_samples
-            .unwrap_or((1 * 1024 * 1024) as u64 / (size_of::<f32>() * 2) as u64);
-
-        return Ok(Self {
+        let mut reader = Reader::new(path, meta_opts, fmt_opts, dec_opts)?;
+        reader.set_upmix_policy(upmix)?;
+        Ok(Self::from_reader(reader, host_buffer_len))
+    }
+
+    /// Same as `new`, but reading from any seekable source, see `Reader::from_media_source`.
+    pub fn from_media_source(
+        source: Box<dyn MediaSource>,
+        hint_ext: Option<&str>,
+        host_buffer_len: usize,
+        upmix: UpmixPolicy,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let mut reader =
+            Reader::from_media_source(source, hint_ext, meta_opts, fmt_opts, dec_opts)?;
+        reader.set_upmix_policy(upmix)?;
+        Ok(Self::from_reader(reader, host_buffer_len))
+    }
+
+    fn from_reader(reader: Reader, host_buffer_len: usize) -> Self {
+        let estimated_size = reader.meta.estimated_capacity();
+
+        Self {
+            source_layout: reader.meta.layout,
+            sample_rate: reader.meta.sample_rate,
             reader,
-            buffer: StereoBuffer::new(estimated_size as usize),
+            buffer: StereoBuffer::new(estimated_size),
             cursor: 0,
+            sample_cursors: [0; 2],
             host_buffer_len,
+            content_len: 0,
+            loop_len: None,
+            align: true,
+            partial_slice_policy: PartialSlicePolicy::default(),
+            growth_policy: GrowthPolicy::default(),
+            collect_packet_stats: false,
+            packet_frame_stats: PacketFrameStats::default(),
+            sum_of_squares: 0.0,
+            decoded_samples: 0,
+            index_timestamps: false,
+            timestamp_index: Vec::new(),
+            seek_index: None,
+            range_frames: None,
+            resampler: None,
+        }
+    }
+
+    /// Same as `new`, but the content is resampled to `target_rate` once read.
+    ///
+    /// The timestamp and seek indexes keep referring to the frames of the source, and the sample
+    /// rate of the buffer is reported by `sample_rate`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_resampled(
+        path: PathBuf,
+        host_buffer_len: usize,
+        upmix: UpmixPolicy,
+        target_rate: u32,
+        quality: ResampleQuality,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let mut full_reader =
+            Self::new(path, host_buffer_len, upmix, meta_opts, fmt_opts, dec_opts)?;
+
+        let source_rate = full_reader.sample_rate;
+        if target_rate != source_rate {
+            full_reader.resampler = Some(match quality {
+                ResampleQuality::Linear => Resampler::new(source_rate, target_rate),
+            });
+            full_reader.sample_rate = target_rate;
+        }
+        Ok(full_reader)
+    }
+
+    /// Sample rate of the buffer, which differs from the one of the source once resampled.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Same as `new`, but only the content between `start` and `end` is read.
+    ///
+    /// The buffer is trimmed, aligned and padded just like a full read. It is left empty when
+    /// `start` is not before `end`, and reading stops at the end of the content when `end` lies
+    /// past it.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_range(
+        path: PathBuf,
+        host_buffer_len: usize,
+        upmix: UpmixPolicy,
+        start: Duration,
+        end: Duration,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let mut full_reader =
+            Self::new(path, host_buffer_len, upmix, meta_opts, fmt_opts, dec_opts)?;
+
+        let range_frames = match start < end {
+            true => {
+                let start_frame = full_reader.reader.seek(start)?;
+                let reader = &full_reader.reader;
+                let end_frame = reader.span_to_frames(reader.duration_to_ts(end));
+                end_frame.saturating_sub(start_frame) as usize
+            }
+            false => 0,
+        };
+
+        // Room for the packet crossing the end of the range
+        let packet_len = full_reader.reader.meta.max_samples_per_packet.unwrap_or(0) as usize;
+        let capacity = std::cmp::min(
+            range_frames + packet_len,
+            full_reader.reader.meta.estimated_capacity(),
+        );
+        full_reader.buffer = StereoBuffer::new(match range_frames {
+            0 => 0,
+            _ => capacity,
         });
+        full_reader.range_frames = Some(range_frames);
+        Ok(full_reader)
     }
-}
 
-impl SampleReader for SyncFullReader {
-    fn read_sync(&mut self) -> Result<(), SampleLoadError> {
-        let mut remainder = StereoBuffer::_0();
-        let known_sample_count = self.reader.meta.n_samples.is_some();
+    /// Frame count statistics of the decoded packets.
+    ///
+    /// Only collected when enabled through the `SyncFullReaderBuilder`, otherwise the count is 0.
+    pub fn packet_frame_stats(&self) -> PacketFrameStats {
+        self.packet_frame_stats
+    }
+
+    /// Whether any decoded packet required a conversion to f32 samples.
+    ///
+    /// Sources decoded straight to f32 (such as float WAVs) take the conversion free path.
+    pub fn used_conversion(&self) -> bool {
+        self.reader.used_conversion()
+    }
+
+    /// Samples decoded beyond [-1, 1], when enabled through the `SyncFullReaderBuilder`.
+    pub fn clipped_sample_count(&self) -> usize {
+        self.reader.clipped_sample_count()
+    }
+
+    /// Source timestamp of the first frame decoded with a clipped sample.
+    pub fn first_clipped_position(&self) -> Option<u64> {
+        self.reader.first_clipped_position()
+    }
+
+    /// Recoverable issues encountered while reading, such as skipped packets.
+    pub fn warnings(&self) -> &[DecodeWarning] {
+        self.reader.warnings()
+    }
+
+    /// RMS of the whole sample over both channels, accumulated while reading.
+    ///
+    /// Only the decoded content is accounted for, not the silence padded for alignment.
+    pub fn overall_rms(&self) -> f32 {
+        if self.decoded_samples == 0 {
+            return 0.0;
+        }
+        (self.sum_of_squares / self.decoded_samples as f64).sqrt() as f32
+    }
+
+    /// Pairs of buffer frame and source timestamp, one for each decoded packet.
+    ///
+    /// The timestamp is expressed in the time base of the track. Positions in between can be
+    /// derived by offsetting from the closest preceding pair.
+    ///
+    /// Only recorded when enabled through the `SyncFullReaderBuilder`, otherwise empty.
+    pub fn timestamp_index(&self) -> &[(usize, u64)] {
+        &self.timestamp_index
+    }
+
+    /// Sparse mapping of source timestamps to buffer frames, built while reading.
+    ///
+    /// Only built when enabled through the `SyncFullReaderBuilder`.
+    pub fn seek_index(&self) -> Option<&SeekIndex> {
+        self.seek_index.as_ref()
+    }
+
+    /// Move the reading cursor to the given time of the decoded content.
+    ///
+    /// The seek index accounts for sources which do not start at timestamp 0, without it the
+    /// content is assumed to start at the first timestamp of the track.
+    ///
+    /// Returns the new cursor, which is clamped to the end of the decoded content.
+    pub fn seek(&mut self, time: Time) -> usize {
+        let meta = &self.reader.meta;
+        let ts = meta.time_base.calc_timestamp(time);
+        let frame = match &self.seek_index {
+            Some(index) => index.frame_of(ts),
+            None => meta.ts_to_frames(meta.start_ts + ts) as usize,
+        };
+        self.cursor = std::cmp::min(frame, self.content_len);
+        self.cursor
+    }
+
+    /// Move the reading cursor to the given frame, clamped into the buffer.
+    pub fn seek_samples(&mut self, sample_index: usize) {
+        let capacity = self.buffer.channel_capacity();
+        self.cursor = std::cmp::min(sample_index, capacity.saturating_sub(1));
+    }
+
+    /// Accumulate the energy of the freshly decoded frames, starting at `from`.
+    fn accumulate_energy(&mut self, from: usize) {
+        let len = self.buffer.cursor() - from;
+        let (left, right) = self.buffer.slice(from, len);
+        self.sum_of_squares += left
+            .iter()
+            .chain(right)
+            .fold(0.0f64, |acc, x| acc + (*x as f64) * (*x as f64));
+        self.decoded_samples += len * 2;
+    }
+
+    /// Same as `read_sync`, calling `f` with the frames of both channels of each decoded packet
+    /// as they are appended, for single pass analysis.
+    ///
+    /// Chunks are issued before the content is cut to the range, resampled or aligned.
+    pub fn read_sync_with(
+        &mut self,
+        mut f: impl FnMut(&[f32], &[f32]),
+    ) -> Result<(), SampleLoadError> {
+        let packet_len = self.reader.meta.max_samples_per_packet.unwrap_or(4096) as usize;
+        let mut remainder = StereoBuffer::new(packet_len);
+        // Ranges do not span the whole content, their size is only an estimate
+        let known_sample_count =
+            self.reader.meta.n_samples.is_some() && self.range_frames.is_none();
         let mut samples_per_packet: usize =
             self.reader.meta.max_samples_per_packet.unwrap_or(0) as usize;
         let mut allocate = false;
 
-        loop {
+        while self.range_frames != Some(0) {
+            let already_written = self.buffer.cursor();
             match self.reader.next_packet(&mut self.buffer, &mut remainder)? {
                 ReadingProjection::EndReached => break,
                 ReadingProjection::SamplesRead(size) => {
+                    // Frame counts may be wrong, even when known
+                    if remainder.has_content() {
+                        self.absorb(&mut remainder);
+                    }
+                    let (left, right) = self
+                        .buffer
+                        .slice(already_written, self.buffer.cursor() - already_written);
+                    f(left, right);
+                    let capacity = remainder.channel_capacity();
+                    if size > capacity {
+                        remainder.reserve_exact(size - capacity);
+                    }
+                    self.accumulate_energy(already_written);
+                    if self.buffer.cursor() > already_written {
+                        let ts = self.reader.decoded_position();
+                        if self.index_timestamps {
+                            self.timestamp_index.push((already_written, ts));
+                        }
+                        if let Some(index) = &mut self.seek_index {
+                            index.insert(ts, already_written);
+                        }
+                    }
+                    if self.collect_packet_stats {
+                        self.packet_frame_stats.push(size);
+                    }
+                    if self
+                        .range_frames
+                        .is_some_and(|range| self.buffer.cursor() >= range)
+                    {
+                        break;
+                    }
                     if known_sample_count {
                         continue;
                     }
@@ -66,29 +353,396 @@ impl SampleReader for SyncFullReader {
             }
 
             if allocate {
-                // Double the size of the buffer and continue
-                self.buffer.reserve(self.buffer.capacity());
+                let capacity = self.buffer.channel_capacity();
+                let additional = self.growth_policy.additional(capacity);
+                self.buffer
+                    .reserve_exact(std::cmp::max(additional, samples_per_packet));
                 allocate = false;
             }
         }
 
+        if let Some(range) = self.range_frames {
+            if self.buffer.cursor() > range {
+                self.buffer.resize(range);
+            }
+        }
+        if let Some(resampler) = &mut self.resampler {
+            let (left, right) = self.buffer.slice(0, self.buffer.cursor());
+            let (mut left, mut right) = resampler.process(left, right);
+            let (tail_left, tail_right) = resampler.finish();
+            left.extend(tail_left);
+            right.extend(tail_right);
+
+            self.buffer = StereoBuffer::new(left.len());
+            self.buffer.append_slices(&left, &right);
+        }
         self.buffer.trim();
-        self.buffer.align_to(self.host_buffer_len);
-        self.buffer.pad_silence();
+        self.content_len = self.buffer.cursor();
+        if self.align {
+            self.buffer.align_to(self.host_buffer_len);
+            self.buffer.pad_silence();
+        }
 
         Ok(())
     }
 
-    fn next_slice(&mut self) -> (&[f32], &[f32]) {
-        let slices = self.buffer.slice(self.cursor, self.host_buffer_len);
-        self.cursor += self.host_buffer_len;
-        if self.cursor > self.buffer.channel_capacity() {
+    /// Move the frames which did not fit into the buffer behind its content, growing it.
+    fn absorb(&mut self, remainder: &mut StereoBuffer) {
+        let frames = remainder.cursor();
+        let capacity = self.buffer.channel_capacity();
+        let additional = self.growth_policy.additional(capacity);
+        self.buffer.reserve_exact(std::cmp::max(additional, frames));
+
+        let (left, right) = remainder.slice(0, frames);
+        self.buffer.append_slices(left, right);
+        remainder.clear_cursor();
+    }
+
+    /// Copy the next host buffer worth of content into the given slices, without allocating.
+    ///
+    /// The tail of the slices is padded with silence once the decoded content runs out.
+    /// Unlike `next_slice`, this does not round robin, every following call reports `End`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slices are not `host_buffer_len` long.
+    pub fn next_into(&mut self, left: &mut [f32], right: &mut [f32]) -> SliceState {
+        assert_eq!(left.len(), self.host_buffer_len);
+        assert_eq!(right.len(), self.host_buffer_len);
+
+        let len = std::cmp::min(
+            self.host_buffer_len,
+            self.content_len.saturating_sub(self.cursor),
+        );
+        let (content_left, content_right) = self.buffer.slice(self.cursor, len);
+        left[..len].copy_from_slice(content_left);
+        right[..len].copy_from_slice(content_right);
+        left[len..].fill(0f32);
+        right[len..].fill(0f32);
+        self.cursor += len;
+
+        if len == self.host_buffer_len {
+            SliceState::Full
+        } else if len > 0 {
+            SliceState::PartiallyPadded(len)
+        } else {
+            SliceState::End
+        }
+    }
+
+    /// Issue the next `n_slices` host buffers worth of content at once, as one contiguous region.
+    ///
+    /// Returns `None`, without advancing, when fewer than `n_slices` host buffers are left before the
+    /// end of the buffer. The rest can still be issued through `next_slice`, which round robins.
+    pub fn next_block(&mut self, n_slices: usize) -> Option<(&[f32], &[f32])> {
+        let len = n_slices * self.host_buffer_len;
+        let capacity = self.buffer.channel_capacity();
+        if self.cursor + len > capacity {
+            return None;
+        }
+
+        let start = self.cursor;
+        self.cursor += len;
+        if self.cursor >= capacity {
             self.cursor = 0;
         }
-        slices
+        Some(self.buffer.slice(start, len))
     }
 
+    /// Crossfade the end of the content into its start over `len` frames, so that round robining
+    /// wraps around without a click. Meant to be called once, after `read_sync`.
+    ///
+    /// The blended frames are dropped from the end, as is the silence padded for alignment. The
+    /// start of the loop is repeated after its end instead, keeping the buffer aligned, and
+    /// `next_slice` wraps around within the loop, so every slice is host buffer long even across
+    /// the loop point. Issuing restarts from the beginning.
+    pub fn set_loop_crossfade(&mut self, len: usize) {
+        self.buffer.resize(self.content_len);
+        self.buffer.crossfade_loop(len);
+        self.buffer.trim();
+        self.content_len = self.buffer.cursor();
+        self.loop_len = None;
+
+        let loop_len = self.content_len;
+        if loop_len > 0 {
+            // Room for a slice starting at the last frame of the loop
+            self.buffer.reserve_exact(self.host_buffer_len);
+            self.buffer.align_to(self.host_buffer_len);
+            while self.buffer.capacity_left() > 0 {
+                let frames = std::cmp::min(loop_len, self.buffer.capacity_left());
+                let start = self.buffer.extract(0, frames);
+                let (left, right) = start.slice(0, frames);
+                self.buffer.append_slices(left, right);
+            }
+            self.loop_len = Some(loop_len);
+        }
+        self.rewind();
+    }
+
+    /// Iterate over the whole buffer in host buffer sized slices, once, for one-shot processing.
+    ///
+    /// Unlike `next_slice`, this neither round robins nor moves the reading cursor. A shorter final
+    /// slice, only possible without alignment or once looped, is handled according to the
+    /// `PartialSlicePolicy`. A crossfaded loop is iterated over once, without its repeated start.
+    pub fn slices(&self) -> impl Iterator<Item = (&[f32], &[f32])> {
+        let host_buffer_len = self.host_buffer_len;
+        let skip_partial = matches!(self.partial_slice_policy, PartialSlicePolicy::Skip);
+        let len = self.loop_len.unwrap_or(self.buffer.channel_capacity());
+        let (left, right) = self.buffer.slice(0, len);
+
+        left.chunks(host_buffer_len)
+            .zip(right.chunks(host_buffer_len))
+            .filter(move |(left, _)| !skip_partial || left.len() == host_buffer_len)
+    }
+
+    pub fn builder(path: PathBuf, host_buffer_len: usize) -> SyncFullReaderBuilder {
+        SyncFullReaderBuilder::new(path, host_buffer_len)
+    }
+}
+
+/// Builder for a `SyncFullReader` with non default reading behaviour.
+pub struct SyncFullReaderBuilder {
+    path: PathBuf,
+    host_buffer_len: usize,
+    meta_opts: MetadataOptions,
+    fmt_opts: FormatOptions,
+    dec_opts: DecoderOptions,
+    align: bool,
+    partial_slice_policy: PartialSlicePolicy,
+    growth_policy: GrowthPolicy,
+    collect_packet_stats: bool,
+    index_timestamps: bool,
+    build_seek_index: bool,
+    channel_map: ChannelMap,
+    downmix_policy: DownmixPolicy,
+    upmix_policy: UpmixPolicy,
+    detect_clipping: bool,
+    recover_on_reset: bool,
+    expected_sample_rate: Option<u32>,
+}
+
+impl SyncFullReaderBuilder {
+    pub fn new(path: PathBuf, host_buffer_len: usize) -> Self {
+        Self {
+            path,
+            host_buffer_len,
+            meta_opts: Default::default(),
+            fmt_opts: Default::default(),
+            dec_opts: Default::default(),
+            align: true,
+            partial_slice_policy: PartialSlicePolicy::default(),
+            growth_policy: GrowthPolicy::default(),
+            collect_packet_stats: false,
+            index_timestamps: false,
+            build_seek_index: false,
+            channel_map: ChannelMap::default(),
+            downmix_policy: DownmixPolicy::default(),
+            upmix_policy: UpmixPolicy::default(),
+            detect_clipping: false,
+            recover_on_reset: false,
+            expected_sample_rate: None,
+        }
+    }
+
+    pub fn meta_opts(mut self, meta_opts: MetadataOptions) -> Self {
+        self.meta_opts = meta_opts;
+        self
+    }
+
+    pub fn fmt_opts(mut self, fmt_opts: FormatOptions) -> Self {
+        self.fmt_opts = fmt_opts;
+        self
+    }
+
+    pub fn dec_opts(mut self, dec_opts: DecoderOptions) -> Self {
+        self.dec_opts = dec_opts;
+        self
+    }
+
+    /// Verify the decoded content against the checksum of the source, if the format has one (FLAC).
+    ///
+    /// A mismatch fails reading with `SampleLoadError::VerificationFailed`. Shortcut for
+    /// `DecoderOptions::verify`.
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.dec_opts.verify = verify;
+        self
+    }
+
+    /// Whether to align the buffer to the host buffer length and pad it with silence. Defaults to `true`.
+    ///
+    /// Without alignment the buffer holds exactly the decoded content, so `next_slice` may issue
+    /// a shorter final slice, which is handled according to the `PartialSlicePolicy`.
+    pub fn align(mut self, align: bool) -> Self {
+        self.align = align;
+        self
+    }
+
+    pub fn partial_slice_policy(mut self, policy: PartialSlicePolicy) -> Self {
+        self.partial_slice_policy = policy;
+        self
+    }
+
+    /// Grow the buffer while reading sources of unknown length. Defaults to `GrowthPolicy::Double`.
+    pub fn growth_policy(mut self, policy: GrowthPolicy) -> Self {
+        self.growth_policy = policy;
+        self
+    }
+
+    /// Collect frame count statistics of the decoded packets. Defaults to `false`.
+    pub fn collect_packet_stats(mut self, collect: bool) -> Self {
+        self.collect_packet_stats = collect;
+        self
+    }
+
+    /// Record the source timestamp of each decoded packet. Defaults to `false`.
+    pub fn index_timestamps(mut self, index: bool) -> Self {
+        self.index_timestamps = index;
+        self
+    }
+
+    /// Build a sparse seek index while reading, used by `SyncFullReader::seek`. Defaults to `false`.
+    pub fn build_seek_index(mut self, build: bool) -> Self {
+        self.build_seek_index = build;
+        self
+    }
+
+    /// Remap the decoded channels. Defaults to `ChannelMap::Identity`.
+    pub fn channel_map(mut self, channel_map: ChannelMap) -> Self {
+        self.channel_map = channel_map;
+        self
+    }
+
+    /// Read sources with more channels than stereo. Defaults to `DownmixPolicy::Reject`.
+    pub fn downmix_policy(mut self, policy: DownmixPolicy) -> Self {
+        self.downmix_policy = policy;
+        self
+    }
+
+    /// Read mono sources into both channels. Defaults to `UpmixPolicy::DuplicateMono`.
+    pub fn upmix_policy(mut self, policy: UpmixPolicy) -> Self {
+        self.upmix_policy = policy;
+        self
+    }
+
+    /// Count the decoded samples beyond [-1, 1], see `Reader::detect_clipping`. Defaults to `false`.
+    pub fn detect_clipping(mut self, detect: bool) -> Self {
+        self.detect_clipping = detect;
+        self
+    }
+
+    /// Reset the decoder when the source requires it, see `Reader::recover_on_reset`. Defaults to `false`.
+    pub fn recover_on_reset(mut self, recover: bool) -> Self {
+        self.recover_on_reset = recover;
+        self
+    }
+
+    /// Fail to build with `SampleLoadError::SampleRateMismatch` unless the source is sampled at
+    /// `rate`, see `Reader::expect_sample_rate`.
+    pub fn expected_sample_rate(mut self, rate: u32) -> Self {
+        self.expected_sample_rate = Some(rate);
+        self
+    }
+
+    pub fn build(self) -> Result<SyncFullReader, SampleLoadError> {
+        let mut reader = Reader::with_downmix_policy(
+            self.path,
+            self.downmix_policy,
+            self.meta_opts,
+            self.fmt_opts,
+            self.dec_opts,
+        )?;
+        if let Some(rate) = self.expected_sample_rate {
+            reader.expect_sample_rate(rate)?;
+        }
+        reader.set_upmix_policy(self.upmix_policy)?;
+        let mut reader = SyncFullReader::from_reader(reader, self.host_buffer_len);
+        reader.align = self.align;
+        reader.partial_slice_policy = self.partial_slice_policy;
+        reader.growth_policy = self.growth_policy;
+        reader.collect_packet_stats = self.collect_packet_stats;
+        reader.index_timestamps = self.index_timestamps;
+        if self.build_seek_index {
+            let interval = reader.reader.meta.sample_rate as f32 * SEEK_INDEX_INTERVAL;
+            reader.seek_index = Some(SeekIndex::new(interval as usize));
+        }
+        reader.reader.channel_map = self.channel_map;
+        reader.reader.detect_clipping(self.detect_clipping);
+        reader.reader.recover_on_reset(self.recover_on_reset);
+        Ok(reader)
+    }
+}
+
+impl SampleReader for SyncFullReader {
+    fn read_sync(&mut self) -> Result<(), SampleLoadError> {
+        self.read_sync_with(|_, _| {})
+    }
+
+    fn next_slice(&mut self) -> Result<(&[f32], &[f32]), SampleLoadError> {
+        // The start of the loop follows its end, slices crossing the loop point are contiguous
+        if let Some(loop_len) = self.loop_len {
+            let start = self.cursor % loop_len;
+            self.cursor = (start + self.host_buffer_len) % loop_len;
+            return Ok(self.buffer.try_slice(start, self.host_buffer_len)?);
+        }
+
+        let capacity = self.buffer.channel_capacity();
+        let mut len = std::cmp::min(self.host_buffer_len, capacity.saturating_sub(self.cursor));
+        if len < self.host_buffer_len {
+            if let PartialSlicePolicy::Skip = self.partial_slice_policy {
+                self.cursor = 0;
+                len = std::cmp::min(self.host_buffer_len, capacity);
+            }
+        }
+
+        let start = self.cursor;
+        self.cursor += len;
+        if self.cursor >= capacity {
+            self.cursor = 0;
+        }
+        Ok(self.buffer.try_slice(start, len)?)
+    }
+
+    /// Move both the slice and the sample cursors back to the start, without decoding again.
+    fn rewind(&mut self) {
+        self.cursor = 0;
+        self.sample_cursors = [0; 2];
+    }
+
+    /// Round robins over the buffer, just like `next_slice`.
+    fn next_sample(&mut self, channel: Channel) -> Result<f32, SampleLoadError> {
+        let len = self.loop_len.unwrap_or(self.buffer.cursor());
+        if len == 0 {
+            return Err(SampleLoadError::UnexpectedState("nothing was read"));
+        }
+
+        let cursor = &mut self.sample_cursors[channel as usize];
+        if *cursor >= len {
+            *cursor = 0;
+        }
+        let samples = match channel {
+            Channel::Left => self.buffer.left.as_slice(),
+            Channel::Right => self.buffer.right.as_slice(),
+        };
+        let sample = samples[*cursor];
+        *cursor += 1;
+        Ok(sample)
+    }
+
+    /// Frames left before issuing round robins back to the start.
+    fn remaining_samples(&self) -> usize {
+        match self.loop_len {
+            Some(loop_len) => loop_len - self.cursor % loop_len,
+            None => self.buffer.channel_capacity().saturating_sub(self.cursor),
+        }
+    }
+
+    /// Share of the frames of a channel issued before round robining, as in `remaining_samples`.
     fn percentage_consumed(&self) -> f32 {
-        self.cursor as f32 / self.buffer.capacity() as f32
+        let len = self.loop_len.unwrap_or(self.buffer.channel_capacity());
+        // Nothing left to issue from an empty buffer
+        if len == 0 {
+            return 1.0;
+        }
+        (len - self.remaining_samples()) as f32 / len as f32
     }
 }
@@ -0,0 +1,78 @@
+use super::{error::SampleLoadError, stream_reader::SyncStreamReader, ReadingProjection};
+
+/// A streaming reader which keeps a window of decoded frames ahead of its read cursor.
+///
+/// Meant for realtime processing which must see upcoming content before issuing the current one,
+/// such as a limiter reacting to peaks ahead of time.
+///
+/// On top of the streaming reader page, up to `lookahead_frames` plus one page worth of frames
+/// per channel are kept in memory.
+pub struct LookaheadReader {
+    stream: SyncStreamReader,
+    lookahead_frames: usize,
+    /// Decoded frames from the read cursor onwards
+    left: Vec<f32>,
+    right: Vec<f32>,
+    end_reached: bool,
+}
+
+impl LookaheadReader {
+    /// Wrap a streaming reader, decoding the first `lookahead_frames` right away.
+    pub fn new(stream: SyncStreamReader, lookahead_frames: usize) -> Result<Self, SampleLoadError> {
+        let mut reader = Self {
+            stream,
+            lookahead_frames,
+            left: Vec::new(),
+            right: Vec::new(),
+            end_reached: false,
+        };
+        reader.fill()?;
+        Ok(reader)
+    }
+
+    /// Decode pages until the lookahead window is full, or the end is reached.
+    fn fill(&mut self) -> Result<(), SampleLoadError> {
+        while self.left.len() < self.lookahead_frames && !self.end_reached {
+            match self.stream.read_next_page()? {
+                ReadingProjection::EndReached => self.end_reached = true,
+                ReadingProjection::SamplesRead(frames) => {
+                    let (left, right) = self.stream.page.slice(0, frames);
+                    self.left.extend_from_slice(left);
+                    self.right.extend_from_slice(right);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The next `frames` frames from the read cursor, without advancing it.
+    ///
+    /// Fewer frames are issued when less content is left, or when more than the lookahead window
+    /// is requested before the end.
+    pub fn peek(&self, frames: usize) -> (&[f32], &[f32]) {
+        let frames = std::cmp::min(frames, self.left.len());
+        (&self.left[..frames], &self.right[..frames])
+    }
+
+    /// Move the read cursor `frames` frames forward, decoding ahead to keep the window full.
+    pub fn advance(&mut self, frames: usize) -> Result<(), SampleLoadError> {
+        let frames = std::cmp::min(frames, self.left.len());
+        self.left.drain(..frames);
+        self.right.drain(..frames);
+        self.fill()
+    }
+
+    /// Frames available ahead of the read cursor.
+    pub fn available(&self) -> usize {
+        self.left.len()
+    }
+
+    /// Whether every decoded frame was issued.
+    pub fn is_finished(&self) -> bool {
+        self.end_reached && self.left.is_empty()
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.stream.sample_rate()
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::PathBuf;
+use symphonia::core::{
+    audio::Layout, codecs::DecoderOptions, formats::FormatOptions, meta::MetadataOptions,
+};
+
+use super::{error::SampleLoadError, MonoSampleReader, Reader, ReadingProjection};
+use crate::buffer::{mono::MonoBuffer, stereo::StereoBuffer, Buffer};
+
+/// A reader which loads the full content of a sample into a single channel.
+///
+/// Only a packet worth of frames is decoded in stereo, so reading mono content takes half the
+/// memory of a `SyncFullReader`. Stereo content is mixed down to the average of both channels.
+///
+/// You should call `read_sync` only once, since it will load the full content of the sample.
+///
+/// Sample issuing will eventually round robin. The total capacity will be a multiple of the host
+/// buffer length.
+pub struct SyncMonoFullReader {
+    pub buffer: MonoBuffer,
+    reader: Reader,
+    /// Reading cursor, not to be confused with the buffer cursor used for writing
+    cursor: usize,
+    host_buffer_len: usize,
+}
+
+impl SyncMonoFullReader {
+    pub fn new(
+        path: PathBuf,
+        host_buffer_len: usize,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let reader = Reader::new(path, meta_opts, fmt_opts, dec_opts)?;
+        let estimated_size = reader.meta.estimated_capacity();
+
+        Ok(Self {
+            buffer: MonoBuffer::new(estimated_size),
+            reader,
+            cursor: 0,
+            host_buffer_len,
+        })
+    }
+
+    /// Append the frames of a decoded packet, reserving more room if needed.
+    fn append_packet(&mut self, packet: &StereoBuffer) {
+        let (left, right) = packet.slice(0, packet.cursor());
+        let frames = left.len();
+
+        let capacity_left = self.buffer.capacity_left();
+        if frames > capacity_left {
+            // At least double the size of the buffer
+            let capacity = self.buffer.channel_capacity();
+            self.buffer
+                .reserve(std::cmp::max(capacity, frames - capacity_left));
+        }
+
+        match self.reader.meta.layout {
+            Layout::Mono => self.buffer.append_slice(left),
+            _ => {
+                let mixed: Vec<f32> = left.iter().zip(right).map(|(l, r)| (l + r) * 0.5).collect();
+                self.buffer.append_slice(&mixed);
+            }
+        }
+    }
+}
+
+impl MonoSampleReader for SyncMonoFullReader {
+    fn read_sync(&mut self) -> Result<(), SampleLoadError> {
+        let packet_len = self.reader.meta.max_samples_per_packet.unwrap_or(4096) as usize;
+        let mut packet = StereoBuffer::new(packet_len);
+        let mut packet_overflow = StereoBuffer::new(packet_len);
+
+        loop {
+            packet.clear_cursor();
+            packet_overflow.clear_cursor();
+
+            match self.reader.next_packet(&mut packet, &mut packet_overflow)? {
+                ReadingProjection::EndReached => break,
+                ReadingProjection::SamplesRead(size) => {
+                    self.append_packet(&packet);
+                    self.append_packet(&packet_overflow);
+
+                    // Make room for bigger packets than anticipated
+                    let capacity = packet.channel_capacity();
+                    if size > capacity {
+                        packet.reserve_exact(size - capacity);
+                        packet_overflow.reserve_exact(size - capacity);
+                    }
+                }
+            }
+        }
+
+        self.buffer.trim();
+        self.buffer.align_to(self.host_buffer_len);
+        self.buffer.pad_silence();
+
+        Ok(())
+    }
+
+    fn next_slice(&mut self) -> Result<&[f32], SampleLoadError> {
+        let capacity = self.buffer.channel_capacity();
+        let len = std::cmp::min(self.host_buffer_len, capacity.saturating_sub(self.cursor));
+
+        let start = self.cursor;
+        self.cursor += len;
+        if self.cursor >= capacity {
+            self.cursor = 0;
+        }
+        Ok(self.buffer.try_slice(start, len)?)
+    }
+
+    fn percentage_consumed(&self) -> f32 {
+        // Nothing left to issue from an empty buffer
+        if self.buffer.capacity() == 0 {
+            return 1.0;
+        }
+        self.cursor as f32 / self.buffer.capacity() as f32
+    }
+}
@@ -1,15 +1,16 @@
-use std::{fs::File, path::PathBuf};
+use std::{fs::File, io::Cursor, mem::size_of, path::PathBuf};
 
 use symphonia::core::{
+    audio::{Channels, Layout},
     codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL},
-    formats::{FormatOptions, FormatReader, Track},
-    io::MediaSourceStream,
-    meta::MetadataOptions,
-    probe::Hint,
+    formats::{FormatOptions, FormatReader, Packet, Track},
+    io::{MediaSource, MediaSourceStream, ReadBytes, SeekBuffered},
+    meta::{Metadata, MetadataLog, MetadataOptions, StandardTagKey, Tag, Value},
+    probe::{Descriptor, Instantiate, QueryDescriptor},
     units::TimeBase,
 };
 
-use super::error::SampleLoadError;
+use super::{error::SampleLoadError, DownmixPolicy};
 
 macro_rules! meta_err {
     ( $x:expr ) => {{
@@ -17,107 +18,393 @@ macro_rules! meta_err {
     }};
 }
 
+/// Where the decoded content comes from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SourceDescriptor {
+    File(PathBuf),
+    /// Bytes held in memory, described by the extension of the file they would be stored as.
+    ///
+    /// The container is identified by its content, the extension is only informative.
+    Memory {
+        len: usize,
+        extension: Option<String>,
+    },
+    /// A custom `MediaSource`, described by an explicit extension.
+    Custom {
+        extension: Option<String>,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct ReaderMeta {
-    pub path: PathBuf,
+    pub source: SourceDescriptor,
     pub delay: u32,
     pub padding: u32,
     pub sample_rate: u32,
+    pub layout: Layout,
     pub start_ts: u64,
     pub time_base: TimeBase,
+    /// Frames per channel a single packet decodes to at most, as stated by the codec
     pub max_samples_per_packet: Option<u64>,
+    /// Frames per channel, excluding the encoder delay and padding
     pub n_samples: Option<u64>,
+    /// Raw tags of the latest metadata revisions found while probing and in the container, preceded
+    /// by the ones of the latest revision found while decoding, which replaces any earlier one
+    pub tags: Vec<Tag>,
+    container_name: &'static str,
+    codec_name: &'static str,
+    seekable: bool,
+    open_warnings: Vec<String>,
 }
 
-fn prepare_media_source(path: &PathBuf) -> Result<MediaSourceStream, SampleLoadError> {
-    match File::open(path) {
-        Ok(file) => Ok(MediaSourceStream::new(Box::new(file), Default::default())),
-        Err(e) => Err(SampleLoadError::IoError(e)),
+impl ReaderMeta {
+    /// Human readable name of the channel layout, such as "Mono" or "Stereo".
+    pub fn layout_name(&self) -> &'static str {
+        match self.layout {
+            Layout::Mono => "Mono",
+            Layout::Stereo => "Stereo",
+            Layout::TwoPointOne => "2.1",
+            Layout::FivePointOne => "5.1",
+        }
+    }
+
+    /// Frames per channel a full read of the source is expected to hold.
+    ///
+    /// Exact when the source states its frame count (`n_samples`), otherwise a 1MB worth of
+    /// stereo f32 frames estimate, which readers grow as needed.
+    pub fn estimated_capacity(&self) -> usize {
+        self.n_samples
+            .unwrap_or((1024 * 1024) as u64 / (size_of::<f32>() * 2) as u64) as usize
+    }
+
+    /// Non fatal issues found while opening the source, such as missing parameters which were defaulted.
+    ///
+    /// Meant as a breadcrumb when a source does not sound right, gapless playback in particular
+    /// depends on the delay and padding being known.
+    pub fn open_warnings(&self) -> &[String] {
+        &self.open_warnings
     }
+
+    /// Human readable name of the container format, such as "WAV" or "Ogg".
+    ///
+    /// This is not the codec, an Ogg container may hold Vorbis or FLAC encoded content.
+    pub fn container_name(&self) -> &'static str {
+        self.container_name
+    }
+
+    /// Short name of the codec, such as "flac", "mp3" or "pcm_s16le", as registered in symphonia.
+    ///
+    /// Stable enough to group sources by, unlike `container_name` it tells encodings apart.
+    pub fn codec_name(&self) -> &'static str {
+        self.codec_name
+    }
+
+    /// Whether the source supports seeking, false for piped sources such as a `ReadOnlySource`.
+    pub fn is_seekable(&self) -> bool {
+        self.seekable
+    }
+
+    /// Convert a frame count from the start of the track into a timestamp in the track's time base.
+    ///
+    /// Uses 128-bit integer intermediates, so multi-hour sources with fine time bases do not
+    /// overflow. The result is rounded to the closest timestamp and offset by `start_ts`.
+    pub fn frames_to_ts(&self, frames: u64) -> u64 {
+        let numer = frames as u128 * self.time_base.denom as u128;
+        let denom = self.sample_rate as u128 * self.time_base.numer as u128;
+        self.start_ts + ((numer + denom / 2) / denom) as u64
+    }
+
+    /// Convert a timestamp in the track's time base into a frame count from the start of the track.
+    ///
+    /// The inverse of `frames_to_ts`, timestamps preceding `start_ts` map to frame 0.
+    pub fn ts_to_frames(&self, ts: u64) -> u64 {
+        let ts = ts.saturating_sub(self.start_ts);
+        let numer = ts as u128 * self.time_base.numer as u128 * self.sample_rate as u128;
+        let denom = self.time_base.denom as u128;
+        ((numer + denom / 2) / denom) as u64
+    }
+
+    /// Look up a string tag by its standard key, regardless of how the container names it.
+    ///
+    /// Trailing nul terminators some containers keep in their values are stripped.
+    pub fn tag(&self, std_key: StandardTagKey) -> Option<&str> {
+        self.tags
+            .iter()
+            .filter(|tag| tag.std_key == Some(std_key))
+            .find_map(|tag| match &tag.value {
+                Value::String(value) => Some(value.trim_end_matches('\0')),
+                _ => None,
+            })
+    }
+}
+
+/// Wrap in memory bytes into a media source stream.
+pub fn prepare_memory_source(data: Vec<u8>) -> MediaSourceStream {
+    MediaSourceStream::new(Box::new(Cursor::new(data)), Default::default())
 }
 
-fn prepare_formatter_hint(path: &PathBuf) -> Hint {
-    let extension = path.extension().and_then(|os_str| os_str.to_str());
-    let mut hint = Hint::new();
-    if let Some(ext) = extension {
-        hint.with_extension(ext);
+pub fn prepare_media_source(path: &PathBuf) -> Result<MediaSourceStream, SampleLoadError> {
+    let file = File::open(path)?;
+    Ok(MediaSourceStream::new(Box::new(file), Default::default()))
+}
+
+/// Probe descriptors of all the container formats symphonia can read.
+fn container_descriptors() -> impl Iterator<Item = &'static Descriptor> {
+    use symphonia::default::formats::*;
+
+    [
+        AdtsReader::query(),
+        FlacReader::query(),
+        IsoMp4Reader::query(),
+        MkvReader::query(),
+        MpaReader::query(),
+        OggReader::query(),
+        WavReader::query(),
+    ]
+    .into_iter()
+    .flatten()
+}
+
+/// Name the container format starting with the given marker, the same way the probe identifies it.
+///
+/// Falls back to "Unknown" when the marker is not fully held by `context`.
+fn container_name(context: &[u8]) -> &'static str {
+    let short_name = container_descriptors()
+        .find(|descriptor| {
+            descriptor
+                .markers
+                .iter()
+                .any(|marker| context.starts_with(marker))
+        })
+        .map(|descriptor| descriptor.short_name);
+
+    match short_name {
+        Some("aac") => "AAC",
+        Some("flac") => "FLAC",
+        Some("isomp4") => "MP4",
+        Some("matroska") => "Matroska",
+        Some("mp1") => "MP1",
+        Some("mp2") => "MP2",
+        Some("mp3") => "MP3",
+        Some("ogg") => "Ogg",
+        Some("wave") => "WAV",
+        _ => "Unknown",
     }
-    hint
 }
 
-type DecodableFormat = (Track, Box<dyn FormatReader>, Box<dyn Decoder>);
+/// Tags of the latest revision of a metadata log.
+fn latest_tags(metadata: Option<Metadata>) -> Vec<Tag> {
+    metadata
+        .and_then(|mut metadata| metadata.skip_to_latest().map(|rev| rev.tags().to_vec()))
+        .unwrap_or_default()
+}
+
+type DecodableFormat = (
+    Track,
+    Box<dyn FormatReader>,
+    Box<dyn Decoder>,
+    Vec<Tag>,
+    &'static str,
+);
 
 fn prepare_sample_decoder(
-    path: &PathBuf,
+    mut media_source: MediaSourceStream,
     meta_opts: &MetadataOptions,
     fmt_opts: &FormatOptions,
     dec_opts: &DecoderOptions,
 ) -> Result<DecodableFormat, SampleLoadError> {
-    // Load the file into a MediaSourceStream
-    let media_source = prepare_media_source(path)?;
-
-    // Get metadata information from the path
-    let hint = prepare_formatter_hint(&path);
-
     // Probe the media source.
-    match symphonia::default::get_probe().format(&hint, media_source, fmt_opts, meta_opts) {
-        Ok(probed) => {
-            // Get the instantiated format reader.
-            let format = probed.format;
-
-            // Find the first audio track with a known (decodeable) codec.
-            match format
-                .tracks()
-                .iter()
-                .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
-            {
-                Some(track) => {
-                    // Create a decoder for the track.
-                    match symphonia::default::get_codecs().make(&track.codec_params, &dec_opts) {
-                        Ok(decoder) => Ok((track.clone(), format, decoder)),
-                        Err(e) => return Err(SampleLoadError::SymphoniaError(e)),
+    // This mirrors `Probe::format`, which does not tell which container it found.
+    // Leading ID3v2 tags are read in full as metadata before the container is searched,
+    // so large embedded pictures do not need to be skipped manually. Pictures exceeding
+    // `MetadataOptions::limit_visual_bytes` are dropped instead of being kept in memory.
+    let probe = symphonia::default::get_probe();
+    let mut metadata = MetadataLog::default();
+    let (mut format, container) = loop {
+        match probe.next(&mut media_source)? {
+            Instantiate::Format(instantiate) => {
+                // The stream is aligned to the start of the container marker, short streams
+                // may not fill the context, their container is then left unnamed
+                let mut context = [0u8; 16];
+                let mut read = 0;
+                while read < context.len() {
+                    match media_source.read_buf(&mut context[read..])? {
+                        0 => break,
+                        len => read += len,
                     }
                 }
-                None => return Err(SampleLoadError::NoSupportedAudioTracks),
+                media_source.seek_buffered_rev(read);
+
+                let format = instantiate(media_source, fmt_opts)?;
+                break (format, container_name(&context[..read]));
             }
+            Instantiate::Metadata(instantiate) => {
+                let mut reader = instantiate(meta_opts);
+                let revision = reader.read_all(&mut media_source)?;
+                metadata.push(revision);
+            }
+        }
+    };
+
+    // Metadata may precede the container (ID3v2) or be part of it (RIFF INFO).
+    let mut tags = latest_tags(Some(metadata.metadata()));
+    tags.extend(latest_tags(Some(format.metadata())));
+
+    // Find the first audio track with a known (decodeable) codec.
+    match format
+        .tracks()
+        .iter()
+        .find(|t| t.codec_params.codec != CODEC_TYPE_NULL)
+    {
+        Some(track) => {
+            // Create a decoder for the track.
+            match symphonia::default::get_codecs().make(&track.codec_params, &dec_opts) {
+                Ok(decoder) => Ok((track.clone(), format, decoder, tags, container)),
+                Err(e) => return Err(e.into()),
+            }
+        }
+        None => return Err(SampleLoadError::NoSupportedAudioTracks),
+    }
+}
+
+/// Infer the layout from the channel count for sources that do not name their layout.
+fn infer_layout(channels: Channels) -> Result<Layout, SampleLoadError> {
+    match channels.count() {
+        1 => Ok(Layout::Mono),
+        2 => Ok(Layout::Stereo),
+        3 => Ok(Layout::TwoPointOne),
+        6 => Ok(Layout::FivePointOne),
+        _ => Err(meta_err!["channel layout"]),
+    }
+}
+
+/// Decode the first packet of the track to find its sample rate, for sources which only state it
+/// in their decoded buffers.
+///
+/// The decoder is reset, so the returned packet can be decoded again from scratch.
+fn probe_sample_rate(
+    track_id: u32,
+    format: &mut dyn FormatReader,
+    decoder: &mut dyn Decoder,
+) -> Result<(u32, Packet), SampleLoadError> {
+    let packet = loop {
+        let packet = format.next_packet()?;
+        if packet.track_id() == track_id {
+            break packet;
         }
-        Err(e) => Err(SampleLoadError::SymphoniaError(e)),
+    };
+
+    let rate = decoder.decode(&packet)?.spec().rate;
+    decoder.reset();
+
+    match rate {
+        0 => Err(meta_err!["sample rate"]),
+        rate => Ok((rate, packet)),
     }
 }
 
-type ReadableFormat = (Track, Box<dyn FormatReader>, Box<dyn Decoder>, ReaderMeta);
+/// The track, its decoders and metadata, along with the packet decoded while probing, if any,
+/// which is the first one to decode.
+type ReadableFormat = (
+    Track,
+    Box<dyn FormatReader>,
+    Box<dyn Decoder>,
+    ReaderMeta,
+    Option<Packet>,
+);
 
 pub fn prepare_sample_reader(
-    path: PathBuf,
+    media_source: MediaSourceStream,
+    source: SourceDescriptor,
+    downmix: DownmixPolicy,
     meta_opts: MetadataOptions,
     fmt_opts: FormatOptions,
     dec_opts: DecoderOptions,
 ) -> Result<ReadableFormat, SampleLoadError> {
-    let (track, reader, decoder) = prepare_sample_decoder(&path, &meta_opts, &fmt_opts, &dec_opts)?;
+    let seekable = media_source.is_seekable();
+    let (track, mut reader, mut decoder, tags, container_name) =
+        prepare_sample_decoder(media_source, &meta_opts, &fmt_opts, &dec_opts)?;
+
+    let codec_params = decoder.codec_params().clone();
+    let mut open_warnings = Vec::new();
 
-    let codec_params = decoder.codec_params();
+    let codec_name = symphonia::default::get_codecs()
+        .get_codec(codec_params.codec)
+        .map_or("unknown", |descriptor| descriptor.short_name);
 
-    let delay = codec_params.delay.unwrap_or(0);
-    let padding = codec_params.padding.unwrap_or(0);
-    let sample_rate = codec_params.sample_rate.ok_or(meta_err!["sample rate"])?;
-    let start_ts = codec_params.start_ts;
-    let time_base = codec_params.time_base.ok_or(meta_err!["time base"])?;
+    let delay = codec_params.delay.unwrap_or_else(|| {
+        open_warnings.push("missing encoder delay, assumed 0".to_string());
+        0
+    });
+    let padding = codec_params.padding.unwrap_or_else(|| {
+        open_warnings.push("missing encoder padding, assumed 0".to_string());
+        0
+    });
+    let (sample_rate, first_packet) = match codec_params.sample_rate {
+        Some(sample_rate) => (sample_rate, None),
+        None => {
+            open_warnings.push("missing sample rate, read from the first packet".to_string());
+            let (sample_rate, packet) =
+                probe_sample_rate(track.id, reader.as_mut(), decoder.as_mut())?;
+            (sample_rate, Some(packet))
+        }
+    };
+    let layout = match codec_params.channel_layout {
+        Some(layout) => layout,
+        None => {
+            let channels = codec_params.channels.ok_or(meta_err!["channels"])?;
+            open_warnings.push(format!(
+                "missing channel layout, inferred from {} channels",
+                channels.count()
+            ));
+            infer_layout(channels)?
+        }
+    };
+    // Only mono and stereo sources can be read into a StereoBuffer as is
+    match (layout, downmix) {
+        (Layout::Mono | Layout::Stereo, _) | (_, DownmixPolicy::Itu) => {}
+        _ => return Err(SampleLoadError::UnsupportedChannelLayout(layout)),
+    }
+    // Packets are timestamped by the container, decoders may restate the time base from the codec
+    let start_ts = track.codec_params.start_ts;
+    let time_base = track
+        .codec_params
+        .time_base
+        .or(codec_params.time_base)
+        .ok_or(meta_err!["time base"])?;
     let max_samples_per_packet = codec_params.max_frames_per_packet;
-    let n_samples = codec_params.n_frames;
+    // With gapless playback enabled, the container already accounts for the delay and padding,
+    // otherwise they are trimmed by the `Reader`
+    let n_samples = match fmt_opts.enable_gapless {
+        true => codec_params.n_frames,
+        false => codec_params
+            .n_frames
+            .map(|n| n.saturating_sub(delay as u64 + padding as u64)),
+    };
+    if n_samples.is_none() {
+        open_warnings.push("missing frame count, the duration is unknown".to_string());
+    }
 
     Ok((
         track,
         reader,
         decoder,
         ReaderMeta {
-            path,
+            source,
             delay,
             padding,
             sample_rate,
+            layout,
             start_ts,
             time_base,
             max_samples_per_packet,
             n_samples,
+            tags,
+            container_name,
+            codec_name,
+            seekable,
+            open_warnings,
         },
+        first_packet,
     ))
 }
@@ -0,0 +1,58 @@
+use std::path::PathBuf;
+use symphonia::core::{
+    codecs::DecoderOptions, formats::FormatOptions, meta::MetadataOptions, units::Time,
+};
+
+use super::{error::SampleLoadError, Reader, ReadingProjection};
+use crate::buffer::{stereo::StereoBuffer, Buffer};
+
+/// A reader which decodes small windows of a sample at arbitrary positions.
+///
+/// The underlying decoder is kept alive between calls, so seeking repeatedly (as a scrubbing UI does)
+/// does not require the file to be opened and probed again.
+pub struct ScrubReader {
+    reader: Reader,
+}
+
+impl ScrubReader {
+    pub fn new(
+        path: PathBuf,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        Ok(Self {
+            reader: Reader::new(path, meta_opts, fmt_opts, dec_opts)?,
+        })
+    }
+
+    /// Decode `frames` samples per channel starting at `ts`.
+    ///
+    /// The window is padded with silence if the end of the sample is reached before it is filled.
+    pub fn window_at(&mut self, ts: Time, frames: usize) -> Result<StereoBuffer, SampleLoadError> {
+        self.reader.seek_time(ts)?;
+
+        let mut window = StereoBuffer::new(frames);
+        // Content decoded past the window is not needed, but it must have room to spill into
+        let packet_len = self.reader.meta.max_samples_per_packet.unwrap_or(4096) as usize;
+        let mut remainder = StereoBuffer::new(std::cmp::max(packet_len, frames));
+
+        while window.capacity_left() > 0 {
+            match self.reader.next_packet(&mut window, &mut remainder)? {
+                ReadingProjection::EndReached => break,
+                ReadingProjection::SamplesRead(size) => {
+                    remainder.clear_cursor();
+                    // Packet sizes may be unknown or wrong, the next packet may be as large
+                    let capacity = remainder.channel_capacity();
+                    if size > capacity {
+                        remainder.reserve_exact(size - capacity);
+                    }
+                }
+            }
+        }
+
+        window.pad_silence();
+
+        Ok(window)
+    }
+}
@@ -0,0 +1,368 @@
+use std::path::PathBuf;
+use symphonia::core::{codecs::DecoderOptions, formats::FormatOptions, meta::MetadataOptions};
+
+use super::{error::SampleLoadError, Reader, ReadingProjection, SampleReader};
+use crate::buffer::{
+    analysis::{BufferStats, StreamingStats},
+    resample::Resampler,
+    stereo::StereoBuffer,
+    Buffer,
+};
+
+/// Share of the ring consumed before `next_slice` refills it
+const DEFAULT_REFILL_THRESHOLD: f32 = 0.5;
+
+/// A reader which decodes a sample one page at a time instead of loading it entirely.
+///
+/// Only the current page and the few frames decoded past it are kept in memory.
+///
+/// As a `SampleReader`, the page acts as a ring of host buffers which `next_slice` refills
+/// transparently, decoding lazily as it is consumed. Do not mix it with `read_next_page`, which
+/// overwrites the page as a whole.
+pub struct SyncStreamReader {
+    /// The most recently read page
+    pub page: StereoBuffer,
+    reader: Reader,
+    page_len: usize,
+    /// Decoded packets are staged here before being moved to the pending frames
+    packet: StereoBuffer,
+    packet_overflow: StereoBuffer,
+    /// Frames decoded (and resampled) past the current page
+    pending_left: Vec<f32>,
+    pending_right: Vec<f32>,
+    resampler: Option<Resampler>,
+    sample_rate: u32,
+    end_reached: bool,
+    /// Frames issued per `next_slice`, the page holds a whole number of them
+    host_buffer_len: usize,
+    /// Position of the next slice in the page
+    slice_cursor: usize,
+    /// Frames of decoded content in the page, the rest is silence
+    page_content: usize,
+    refill_threshold: f32,
+    /// Statistics of all the pages read so far
+    stats: StreamingStats,
+}
+
+impl SyncStreamReader {
+    pub fn new(
+        path: PathBuf,
+        page_len: usize,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let reader = Reader::new(path, meta_opts, fmt_opts, dec_opts)?;
+        let packet_len = reader
+            .meta
+            .max_samples_per_packet
+            .map_or(page_len, |max| max as usize);
+        let sample_rate = reader.meta.sample_rate;
+
+        Ok(Self {
+            page: StereoBuffer::new(page_len),
+            reader,
+            page_len,
+            packet: StereoBuffer::new(packet_len),
+            packet_overflow: StereoBuffer::new(packet_len),
+            pending_left: Vec::new(),
+            pending_right: Vec::new(),
+            resampler: None,
+            sample_rate,
+            end_reached: false,
+            host_buffer_len: page_len,
+            slice_cursor: page_len,
+            page_content: 0,
+            refill_threshold: DEFAULT_REFILL_THRESHOLD,
+            stats: StreamingStats::new(),
+        })
+    }
+
+    /// Same as `new`, but every page is resampled to `target_rate`.
+    ///
+    /// The resampler state is carried across pages, so the output is continuous at page boundaries.
+    pub fn new_resampled(
+        path: PathBuf,
+        page_len: usize,
+        target_rate: u32,
+        meta_opts: MetadataOptions,
+        fmt_opts: FormatOptions,
+        dec_opts: DecoderOptions,
+    ) -> Result<Self, SampleLoadError> {
+        let mut reader = Self::new(path, page_len, meta_opts, fmt_opts, dec_opts)?;
+        if target_rate != reader.sample_rate {
+            reader.resampler = Some(Resampler::new(reader.sample_rate, target_rate));
+            reader.sample_rate = target_rate;
+        }
+        Ok(reader)
+    }
+
+    /// Sample rate of the issued pages.
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Statistics of the content of all the pages read so far, excluding the padded silence.
+    pub fn stats(&self) -> BufferStats {
+        self.stats.finish()
+    }
+
+    /// Decode the next packet and queue its frames behind the current page.
+    fn decode_packet(&mut self) -> Result<(), SampleLoadError> {
+        self.packet.clear_cursor();
+        self.packet_overflow.clear_cursor();
+
+        let size = match self
+            .reader
+            .next_packet(&mut self.packet, &mut self.packet_overflow)?
+        {
+            ReadingProjection::EndReached => {
+                self.end_reached = true;
+                // The frames held back by the resampler follow the last packet
+                if let Some(resampler) = &mut self.resampler {
+                    let (left, right) = resampler.finish();
+                    self.pending_left.extend(left);
+                    self.pending_right.extend(right);
+                }
+                return Ok(());
+            }
+            ReadingProjection::SamplesRead(size) => size,
+        };
+
+        let (packet_left, packet_right) = self.packet.slice(0, self.packet.cursor());
+        let (overflow_left, overflow_right) =
+            self.packet_overflow.slice(0, self.packet_overflow.cursor());
+        let left = [packet_left, overflow_left].concat();
+        let right = [packet_right, overflow_right].concat();
+
+        // Make room for bigger packets than anticipated
+        let capacity = self.packet.channel_capacity();
+        if size > capacity {
+            self.packet.reserve_exact(size - capacity);
+            self.packet_overflow.reserve_exact(size - capacity);
+        }
+
+        match &mut self.resampler {
+            Some(resampler) => {
+                let (left, right) = resampler.process(&left, &right);
+                self.pending_left.extend(left);
+                self.pending_right.extend(right);
+            }
+            None => {
+                self.pending_left.extend(left);
+                self.pending_right.extend(right);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Read the next page of `page_len` frames into `page`.
+    ///
+    /// The last page is padded with silence. Returns `EndReached` once there is nothing left to read.
+    pub fn read_next_page(&mut self) -> Result<ReadingProjection, SampleLoadError> {
+        while self.pending_left.len() < self.page_len && !self.end_reached {
+            self.decode_packet()?;
+        }
+
+        let frames = std::cmp::min(self.page_len, self.pending_left.len());
+        if frames == 0 {
+            return Ok(ReadingProjection::EndReached);
+        }
+
+        self.page.clear_cursor();
+        self.page
+            .append_slices(&self.pending_left[..frames], &self.pending_right[..frames]);
+        self.page.pad_silence();
+
+        self.stats.push(&self.pending_left[..frames]);
+        self.stats.push(&self.pending_right[..frames]);
+
+        self.pending_left.drain(..frames);
+        self.pending_right.drain(..frames);
+
+        Ok(ReadingProjection::SamplesRead(frames))
+    }
+
+    pub fn builder(path: PathBuf, host_buffer_len: usize) -> SyncStreamReaderBuilder {
+        SyncStreamReaderBuilder::new(path, host_buffer_len)
+    }
+}
+
+/// Builder for a `SyncStreamReader` issuing host buffers through `SampleReader`.
+pub struct SyncStreamReaderBuilder {
+    path: PathBuf,
+    host_buffer_len: usize,
+    meta_opts: MetadataOptions,
+    fmt_opts: FormatOptions,
+    dec_opts: DecoderOptions,
+    ring_slices: usize,
+    refill_threshold: f32,
+    target_rate: Option<u32>,
+    recover_on_reset: bool,
+    expected_sample_rate: Option<u32>,
+}
+
+impl SyncStreamReaderBuilder {
+    pub fn new(path: PathBuf, host_buffer_len: usize) -> Self {
+        Self {
+            path,
+            host_buffer_len,
+            meta_opts: Default::default(),
+            fmt_opts: Default::default(),
+            dec_opts: Default::default(),
+            ring_slices: 4,
+            refill_threshold: DEFAULT_REFILL_THRESHOLD,
+            target_rate: None,
+            recover_on_reset: false,
+            expected_sample_rate: None,
+        }
+    }
+
+    pub fn meta_opts(mut self, meta_opts: MetadataOptions) -> Self {
+        self.meta_opts = meta_opts;
+        self
+    }
+
+    pub fn fmt_opts(mut self, fmt_opts: FormatOptions) -> Self {
+        self.fmt_opts = fmt_opts;
+        self
+    }
+
+    pub fn dec_opts(mut self, dec_opts: DecoderOptions) -> Self {
+        self.dec_opts = dec_opts;
+        self
+    }
+
+    /// Host buffers held by the ring. Defaults to 4.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slices` is 0.
+    pub fn ring_slices(mut self, slices: usize) -> Self {
+        assert!(slices > 0);
+        self.ring_slices = slices;
+        self
+    }
+
+    /// Share of the ring (between 0 and 1) consumed before `next_slice` refills it. Defaults to `0.5`.
+    pub fn refill_threshold(mut self, threshold: f32) -> Self {
+        self.refill_threshold = threshold.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Resample the content to `target_rate`, see `SyncStreamReader::new_resampled`.
+    pub fn target_rate(mut self, target_rate: u32) -> Self {
+        self.target_rate = Some(target_rate);
+        self
+    }
+
+    /// Reset the decoder when the source requires it, see `Reader::recover_on_reset`. Defaults to `false`.
+    pub fn recover_on_reset(mut self, recover: bool) -> Self {
+        self.recover_on_reset = recover;
+        self
+    }
+
+    /// Fail to build with `SampleLoadError::SampleRateMismatch` unless the source is sampled at
+    /// `rate`, see `Reader::expect_sample_rate`.
+    pub fn expected_sample_rate(mut self, rate: u32) -> Self {
+        self.expected_sample_rate = Some(rate);
+        self
+    }
+
+    pub fn build(self) -> Result<SyncStreamReader, SampleLoadError> {
+        let page_len = self.host_buffer_len * self.ring_slices;
+        let mut reader = match self.target_rate {
+            Some(target_rate) => SyncStreamReader::new_resampled(
+                self.path,
+                page_len,
+                target_rate,
+                self.meta_opts,
+                self.fmt_opts,
+                self.dec_opts,
+            )?,
+            None => SyncStreamReader::new(
+                self.path,
+                page_len,
+                self.meta_opts,
+                self.fmt_opts,
+                self.dec_opts,
+            )?,
+        };
+        if let Some(rate) = self.expected_sample_rate {
+            reader.reader.expect_sample_rate(rate)?;
+        }
+        reader.host_buffer_len = self.host_buffer_len;
+        reader.refill_threshold = self.refill_threshold;
+        reader.reader.recover_on_reset(self.recover_on_reset);
+        Ok(reader)
+    }
+}
+
+impl SampleReader for SyncStreamReader {
+    /// Prime the ring with its first frames, without draining the source.
+    fn read_sync(&mut self) -> Result<(), SampleLoadError> {
+        self.refill()
+    }
+
+    /// Issue the next host buffer of the ring, refilling it first once the threshold is crossed.
+    ///
+    /// The tail is padded with silence once the content runs out.
+    fn next_slice(&mut self) -> Result<(&[f32], &[f32]), SampleLoadError> {
+        if self.needs_refill(self.refill_threshold) {
+            if let Err(e) = self.refill() {
+                // Report the error once, and issue silence from the next call on
+                self.end_reached = true;
+                self.pending_left.clear();
+                self.pending_right.clear();
+                self.page_content = 0;
+                self.slice_cursor = 0;
+                let (left, right) = self.page.as_slice_mut();
+                left.fill(0f32);
+                right.fill(0f32);
+                return Err(e);
+            }
+        }
+
+        let start = self.slice_cursor;
+        self.slice_cursor += self.host_buffer_len;
+        Ok(self.page.try_slice(start, self.host_buffer_len)?)
+    }
+
+    fn percentage_consumed(&self) -> f32 {
+        self.slice_cursor as f32 / self.page_len as f32
+    }
+
+    /// Frames left in the ring before it must be refilled.
+    fn remaining_samples(&self) -> usize {
+        self.page_len.saturating_sub(self.slice_cursor)
+    }
+
+    /// Move the unissued frames to the front of the ring, and decode the next ones behind them.
+    fn refill(&mut self) -> Result<(), SampleLoadError> {
+        let consumed = std::cmp::min(self.slice_cursor, self.page_len);
+        let kept = self.page_content.saturating_sub(consumed);
+
+        while self.pending_left.len() < self.page_len - kept && !self.end_reached {
+            self.decode_packet()?;
+        }
+        let frames = std::cmp::min(self.page_len - kept, self.pending_left.len());
+
+        // Positions in the ring are tracked here, the page cursor is not used
+        let (left, right) = self.page.as_slice_mut();
+        for (channel, pending) in [(left, &self.pending_left), (right, &self.pending_right)] {
+            channel.copy_within(consumed..consumed + kept, 0);
+            channel[kept..kept + frames].copy_from_slice(&pending[..frames]);
+            channel[kept + frames..].fill(0f32);
+        }
+
+        self.stats.push(&self.pending_left[..frames]);
+        self.stats.push(&self.pending_right[..frames]);
+        self.pending_left.drain(..frames);
+        self.pending_right.drain(..frames);
+
+        self.slice_cursor = 0;
+        self.page_content = kept + frames;
+        Ok(())
+    }
+}
@@ -0,0 +1,266 @@
+use std::path::Path;
+
+use hound::{SampleFormat, WavSpec, WavWriter};
+use symphonia::core::audio::Layout;
+
+use crate::{
+    buffer::{mono::MonoBuffer, stereo::StereoBuffer, Buffer},
+    reader::{error::SampleLoadError, Reader, ReadingProjection},
+};
+
+/// Sample format of a transcoded WAV file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WavFormat {
+    Int16,
+    Int24,
+    Float32,
+}
+
+impl WavFormat {
+    fn bits_per_sample(self) -> u16 {
+        match self {
+            WavFormat::Int16 => 16,
+            WavFormat::Int24 => 24,
+            WavFormat::Float32 => 32,
+        }
+    }
+
+    /// Integer formats up to 24 bits, floats at 32 bits.
+    fn from_bits(bits: u16) -> Option<Self> {
+        match bits {
+            16 => Some(WavFormat::Int16),
+            24 => Some(WavFormat::Int24),
+            32 => Some(WavFormat::Float32),
+            _ => None,
+        }
+    }
+
+    fn spec(self, channels: u16, sample_rate: u32) -> WavSpec {
+        WavSpec {
+            channels,
+            sample_rate,
+            bits_per_sample: self.bits_per_sample(),
+            sample_format: match self {
+                WavFormat::Float32 => SampleFormat::Float,
+                _ => SampleFormat::Int,
+            },
+        }
+    }
+}
+
+/// Noise added to samples before they are quantized to integers, to decorrelate the
+/// quantization error from quiet content.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dither {
+    /// Quantize as is.
+    #[default]
+    None,
+    /// Uniform noise of one least significant bit peak to peak.
+    Rectangular,
+    /// Triangular noise of two least significant bits peak to peak, the sum of two uniform draws.
+    TriangularPdf,
+}
+
+/// Deterministic noise source for a `Dither`, a splitmix64 generator.
+struct DitherNoise {
+    dither: Dither,
+    state: u64,
+}
+
+impl DitherNoise {
+    fn new(dither: Dither, seed: u64) -> Self {
+        Self {
+            dither,
+            state: seed,
+        }
+    }
+
+    /// Uniform in [-0.5, 0.5)
+    fn uniform(&mut self) -> f32 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 40) as f32 / (1u32 << 24) as f32 - 0.5
+    }
+
+    /// Quantize `sample` to an integer of full scale `scale`.
+    ///
+    /// The sample is rounded to the nearest integer, after adding the dither noise in least
+    /// significant bits if any.
+    fn quantize(&mut self, sample: f32, scale: f32) -> i32 {
+        let scaled = sample.clamp(-1.0, 1.0) * scale;
+        let noise = match self.dither {
+            Dither::None => return scaled.round() as i32,
+            Dither::Rectangular => self.uniform(),
+            Dither::TriangularPdf => self.uniform() + self.uniform(),
+        };
+        (scaled + noise).round().clamp(-scale - 1.0, scale) as i32
+    }
+}
+
+/// Frames decoded per packet when the source does not state it
+const DEFAULT_PACKET_FRAMES: usize = 4096;
+
+/// Write a slice of frames, interleaved, in the requested format.
+fn write_frames<W>(
+    writer: &mut WavWriter<W>,
+    format: WavFormat,
+    noise: &mut DitherNoise,
+    channels: &[&[f32]],
+) -> Result<(), hound::Error>
+where
+    W: std::io::Write + std::io::Seek,
+{
+    for frame in 0..channels[0].len() {
+        for channel in channels {
+            let sample = channel[frame];
+            match format {
+                WavFormat::Int16 => {
+                    writer.write_sample(noise.quantize(sample, i16::MAX as f32) as i16)?
+                }
+                WavFormat::Int24 => writer.write_sample(noise.quantize(sample, 8_388_607.0))?,
+                WavFormat::Float32 => writer.write_sample(sample)?,
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Decode `input` and write it to the WAV file `output`, without holding the whole content in memory.
+///
+/// Mono sources are written as mono, everything else as stereo.
+/// Returns the number of frames written.
+pub fn transcode_to_wav(
+    input: &Path,
+    output: &Path,
+    format: WavFormat,
+) -> Result<u64, SampleLoadError> {
+    transcode_to_wav_dithered(input, output, format, Dither::None, 0)
+}
+
+/// Same as `transcode_to_wav`, applying `dither` when writing integers.
+///
+/// The noise is generated from `seed`, the same seed always writes the same file.
+pub fn transcode_to_wav_dithered(
+    input: &Path,
+    output: &Path,
+    format: WavFormat,
+    dither: Dither,
+    seed: u64,
+) -> Result<u64, SampleLoadError> {
+    let mut reader = Reader::new(
+        input.to_path_buf(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )?;
+
+    let mono = matches!(reader.meta.layout, Layout::Mono);
+    let spec = format.spec(if mono { 1 } else { 2 }, reader.meta.sample_rate);
+    let mut writer = WavWriter::create(output, spec).map_err(SampleLoadError::WavWriteError)?;
+
+    let packet_frames = reader
+        .meta
+        .max_samples_per_packet
+        .map_or(DEFAULT_PACKET_FRAMES, |max| max as usize);
+    let mut packet = StereoBuffer::new(packet_frames);
+    let mut overflow = StereoBuffer::new(packet_frames);
+    let mut frames = 0u64;
+    let mut noise = DitherNoise::new(dither, seed);
+
+    loop {
+        packet.clear_cursor();
+        overflow.clear_cursor();
+
+        let size = match reader.next_packet(&mut packet, &mut overflow)? {
+            ReadingProjection::EndReached => break,
+            ReadingProjection::SamplesRead(size) => size,
+        };
+
+        for buffer in [&packet, &overflow] {
+            let (left, right) = buffer.slice(0, buffer.cursor());
+            let channels: &[&[f32]] = if mono { &[left] } else { &[left, right] };
+            write_frames(&mut writer, format, &mut noise, channels)
+                .map_err(SampleLoadError::WavWriteError)?;
+        }
+        frames += size as u64;
+
+        // Make room for bigger packets than anticipated
+        let capacity = packet.channel_capacity();
+        if size > capacity {
+            packet.reserve_exact(size - capacity);
+            overflow.reserve_exact(size - capacity);
+        }
+    }
+
+    writer.finalize().map_err(SampleLoadError::WavWriteError)?;
+
+    Ok(frames)
+}
+
+/// Write the given channels to the WAV file `path`, `bits` selecting the format.
+fn write_wav(
+    path: &Path,
+    sample_rate: u32,
+    bits: u16,
+    noise: &mut DitherNoise,
+    channels: &[&[f32]],
+) -> Result<(), hound::Error> {
+    let format = WavFormat::from_bits(bits).ok_or(hound::Error::Unsupported)?;
+    let spec = format.spec(channels.len() as u16, sample_rate);
+    let mut writer = WavWriter::create(path, spec)?;
+    write_frames(&mut writer, format, noise, channels)?;
+    writer.finalize()
+}
+
+impl StereoBuffer {
+    /// Write the written region to the WAV file `path`, interleaved.
+    ///
+    /// 16 and 24 bits are written as integers, clamping samples to [-1, 1], and 32 bits as floats.
+    /// Other bit depths fail with `hound::Error::Unsupported`.
+    pub fn write_wav(&self, path: &Path, sample_rate: u32, bits: u16) -> Result<(), hound::Error> {
+        self.write_wav_dithered(path, sample_rate, bits, Dither::None, 0)
+    }
+
+    /// Same as `write_wav`, applying `dither` generated from `seed` when writing integers.
+    pub fn write_wav_dithered(
+        &self,
+        path: &Path,
+        sample_rate: u32,
+        bits: u16,
+        dither: Dither,
+        seed: u64,
+    ) -> Result<(), hound::Error> {
+        let (left, right) = self.slice(0, self.cursor());
+        let mut noise = DitherNoise::new(dither, seed);
+        write_wav(path, sample_rate, bits, &mut noise, &[left, right])
+    }
+}
+
+impl MonoBuffer {
+    /// Write the written region to the mono WAV file `path`, see `StereoBuffer::write_wav`.
+    pub fn write_wav(&self, path: &Path, sample_rate: u32, bits: u16) -> Result<(), hound::Error> {
+        self.write_wav_dithered(path, sample_rate, bits, Dither::None, 0)
+    }
+
+    /// Same as `write_wav`, applying `dither` generated from `seed` when writing integers.
+    pub fn write_wav_dithered(
+        &self,
+        path: &Path,
+        sample_rate: u32,
+        bits: u16,
+        dither: Dither,
+        seed: u64,
+    ) -> Result<(), hound::Error> {
+        let mut noise = DitherNoise::new(dither, seed);
+        write_wav(
+            path,
+            sample_rate,
+            bits,
+            &mut noise,
+            &[self.slice(0, self.cursor())],
+        )
+    }
+}
@@ -0,0 +1,59 @@
+mod common;
+
+use audio_reader::prelude::*;
+use common::*;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Counts the bytes allocated by the test binary
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::SeqCst);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Decoding a packet of a float source must not allocate a converted copy of it, even when
+/// leading frames are dropped after a seek.
+#[test]
+fn decode_float_packets_without_conversion() {
+    stereo_float_sine();
+
+    let mut reader = Reader::new(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    let packet_frames = reader.meta.max_samples_per_packet.unwrap() as usize;
+    let packet_bytes = packet_frames * 2 * std::mem::size_of::<f32>();
+
+    let mut buffer = StereoBuffer::new(SAMPLE_RATE as usize);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+
+    // Prerolling lands a few packets early, their frames are dropped by the next reads
+    reader.seek_accurate(Duration::from_millis(500), 4).unwrap();
+
+    let before = ALLOCATED.load(Ordering::SeqCst);
+    reader.next_packet(&mut buffer, &mut remainder).unwrap();
+    let allocated = ALLOCATED.load(Ordering::SeqCst) - before;
+
+    // The demuxer allocates the encoded packet, which is as large as its decoded content
+    assert!(allocated < packet_bytes + packet_bytes / 2);
+    assert!(!reader.used_conversion());
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "rayon")]
+
+mod common;
+
+use audio_reader::{prelude::*, reader::batch::load_many};
+use common::*;
+
+use std::path::PathBuf;
+use symphonia::core::audio::Layout;
+
+#[test]
+fn load_many_in_order() {
+    mono_int_sine();
+    stereo_float_sine();
+    let paths = vec![
+        PathBuf::from(INT_MONO_SINE),
+        PathBuf::from("assets/missing.wav"),
+        PathBuf::from(FLOAT_STEREO_SINE),
+    ];
+
+    let mut readers = load_many(
+        paths,
+        HOST_BUFFER_SIZE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    );
+    assert_eq!(readers.len(), 3);
+
+    // A missing file only fails its own slot
+    assert!(matches!(readers[1], Err(SampleLoadError::IoError(_))));
+
+    let mono = readers[0].as_ref().unwrap();
+    assert!(matches!(mono.source_layout, Layout::Mono));
+
+    let stereo = readers[2].as_mut().unwrap();
+    assert!(matches!(stereo.source_layout, Layout::Stereo));
+    let (left, right) = stereo.next_slice().unwrap();
+    let pregen_sine = sine_float_samples();
+    chunked_error_asssert(left, &pregen_sine[..HOST_BUFFER_SIZE], 64, ACCEPTABLE_ERROR);
+    chunked_error_asssert(
+        right,
+        &pregen_sine[..HOST_BUFFER_SIZE],
+        64,
+        ACCEPTABLE_ERROR,
+    );
+}
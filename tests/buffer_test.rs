@@ -21,6 +21,17 @@ fn create_buffer() {
     assert_eq!(buffer.len(), 10);
 }
 
+#[test]
+fn allocate_silence() {
+    assert!(buffer_with_size(10).iter().all(|&x| x == 0f32));
+
+    let mut buffer = MonoBuffer::new(4);
+    buffer.append_slice(&[1.0; 4]);
+    buffer.reserve_exact(4);
+    assert!(buffer.channel_capacity() >= 8);
+    assert!(buffer.as_slice()[4..].iter().all(|&x| x == 0f32));
+}
+
 #[test]
 fn create_stereo_buffer() {
     let buffer = StereoBuffer::new(10);
@@ -152,3 +163,876 @@ fn trim_buffer() {
     assert_eq!(buffer.left.capacity(), buffer.right.capacity());
     assert_eq!(buffer.left.buf.len(), buffer.right.buf.len());
 }
+
+#[test]
+fn extract_region() {
+    let mut buffer = StereoBuffer::new(20);
+    buffer.append_slices(&a_test_vec(15), &b_test_vec(15));
+
+    let extracted = buffer.extract(5, 10);
+
+    assert_eq!(extracted.left.buf, a_test_vec(15)[5..]);
+    assert_eq!(extracted.right.buf, b_test_vec(15)[5..]);
+    assert_eq!(extracted.cursor(), 10);
+    assert_eq!(extracted.capacity_left(), 0);
+    assert_eq!(buffer.cursor(), 15);
+}
+
+#[test]
+#[should_panic]
+fn extract_past_written_region() {
+    let mut buffer = StereoBuffer::new(20);
+    buffer.append_slices(&a_test_vec(15), &b_test_vec(15));
+    buffer.extract(10, 10);
+}
+
+#[test]
+fn prepend_silence_to_content() {
+    let mut buffer = StereoBuffer::new(10);
+    buffer.append_slices(&a_test_vec(8), &b_test_vec(8));
+    buffer.prepend_silence(5);
+
+    assert_eq!(buffer.cursor(), 13);
+    assert_eq!(buffer.left.capacity(), buffer.right.capacity());
+    assert!(buffer.left[..5].iter().all(|&x| x == 0f32));
+    assert!(buffer.right[..5].iter().all(|&x| x == 0f32));
+    assert_eq!(buffer.left[5..13], a_test_vec(8));
+    assert_eq!(buffer.right[5..13], b_test_vec(8));
+}
+
+#[test]
+fn try_append_only_when_fitting() {
+    let mut buffer = StereoBuffer::new(10);
+
+    assert!(buffer.try_append_slices(&a_test_vec(6), &b_test_vec(6)));
+    assert_eq!(buffer.cursor(), 6);
+
+    assert!(!buffer.try_append_slices(&a_test_vec(5), &b_test_vec(5)));
+    assert_eq!(buffer.cursor(), 6);
+
+    assert!(buffer.try_append_slices(&a_test_vec(4), &b_test_vec(4)));
+    assert_eq!(buffer.capacity_left(), 0);
+}
+
+#[test]
+fn scale_stereo_width() {
+    let mut buffer = StereoBuffer::new(10);
+    buffer.append_slices(&a_test_vec(8), &b_test_vec(8));
+
+    buffer.set_width(1.0);
+    assert_eq!(buffer.left[..8], a_test_vec(8));
+    assert_eq!(buffer.right[..8], b_test_vec(8));
+
+    buffer.set_width(2.0);
+    assert_eq!(buffer.left[0], -2.5);
+    assert_eq!(buffer.right[0], 11.5);
+
+    buffer.set_width(0.0);
+    assert_eq!(buffer.left[..8], buffer.right[..8]);
+    assert!(buffer.left[..8].iter().all(|&x| x == 4.5));
+}
+
+#[test]
+fn accumulate_stats_incrementally() {
+    let mut left = noise(1000, 1);
+    let right = noise(1000, 2);
+    left[10] = -1.5;
+    left[20] = 1.0;
+
+    let mut buffer = StereoBuffer::new(1000);
+    buffer.append_slices(&left, &right);
+    let stats = buffer.stats();
+
+    assert_eq!(stats.peak, 1.5);
+    assert_eq!(stats.samples, 2000);
+    // Full scale is not clipped
+    assert_eq!(stats.clipped, 1);
+
+    let mut streaming = StreamingStats::new();
+    for (left, right) in left.chunks(128).zip(right.chunks(128)) {
+        streaming.push(left);
+        streaming.push(right);
+    }
+    let streamed = streaming.finish();
+
+    assert_eq!(streamed.peak, stats.peak);
+    assert_eq!(streamed.samples, stats.samples);
+    assert_eq!(streamed.clipped, stats.clipped);
+    assert!((streamed.rms - stats.rms).abs() < 1e-6);
+    assert_eq!(StreamingStats::new().finish(), BufferStats::default());
+}
+
+#[test]
+fn export_planar_matrix() {
+    let mut buffer = StereoBuffer::new(10);
+    buffer.append_slices(&a_test_vec(6), &b_test_vec(6));
+
+    let (channels, data) = buffer.to_matrix();
+
+    assert_eq!(channels, 2);
+    assert_eq!(data.len(), 2 * 6);
+    assert_eq!(data[..6], a_test_vec(6));
+    assert_eq!(data[6..], b_test_vec(6));
+}
+
+#[test]
+fn loop_extend_to_target_length() {
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&a_test_vec(4), &b_test_vec(4));
+    buffer.loop_extend(10, 0);
+
+    assert_eq!(buffer.cursor(), 10);
+    assert_eq!(buffer.left[..10], [1., 2., 3., 4., 1., 2., 3., 4., 1., 2.]);
+    assert_eq!(buffer.right[..10], [4., 3., 2., 1., 4., 3., 2., 1., 4., 3.]);
+
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&a_test_vec(4), &b_test_vec(4));
+    buffer.loop_extend(10, 1);
+
+    assert_eq!(
+        buffer.left[..10],
+        [1., 2., 3., 2.5, 2., 3., 2.5, 2., 3., 4.]
+    );
+
+    buffer.loop_extend(3, 1);
+    assert_eq!(buffer.cursor(), 3);
+}
+
+#[test]
+fn loop_extend_empty_buffer() {
+    let mut buffer = StereoBuffer::new(0);
+    buffer.loop_extend(5, 2);
+
+    assert_eq!(buffer.cursor(), 5);
+    assert!(buffer.left[..5].iter().all(|&x| x == 0f32));
+    assert!(buffer.right[..5].iter().all(|&x| x == 0f32));
+}
+
+#[test]
+fn crossfade_loop_boundary() {
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&a_test_vec(4), &b_test_vec(4));
+    buffer.crossfade_loop(1);
+
+    assert_eq!(buffer.cursor(), 3);
+    assert_eq!(buffer.left[..3], [2.5, 2., 3.]);
+    assert_eq!(buffer.right[..3], [2.5, 3., 2.]);
+
+    // At least one frame is left
+    buffer.crossfade_loop(10);
+    assert_eq!(buffer.cursor(), 1);
+}
+
+#[test]
+fn find_silence_regions() {
+    let left = [0.5, 0.0, 0.0, 0.0, 0.5, 0.001, 0.0, 0.5, 0.0, 0.0, 0.0];
+    let right = [0.5, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.5, 0.0, 0.0, 0.0];
+    let mut buffer = StereoBuffer::new(20);
+    buffer.append_slices(&left, &right);
+
+    assert_eq!(buffer.silence_regions(0.01, 3), vec![(1, 4), (8, 11)]);
+    assert_eq!(
+        buffer.silence_regions(0.01, 2),
+        vec![(1, 4), (5, 7), (8, 11)]
+    );
+    assert_eq!(buffer.silence_regions(0.0, 2), vec![(1, 4), (8, 11)]);
+    assert!(buffer.silence_regions(0.01, 4).is_empty());
+}
+
+#[test]
+fn trim_leading_and_trailing_silence() {
+    let sine: Vec<f32> = (0..100).map(|i| (0.1 * i as f32).sin() + 0.5).collect();
+    let mut left = vec![0.0; 30];
+    left.extend(&sine);
+    left.extend([0.0; 50]);
+    let mut right = vec![0.0; 180];
+    // A frame is only silent if both channels are
+    right[25] = 0.5;
+
+    let mut buffer = StereoBuffer::new(200);
+    buffer.append_slices(&left, &right);
+    assert_eq!(buffer.trim_silence(0.01, 64), 25);
+
+    assert_eq!(buffer.cursor(), 105);
+    assert_eq!(buffer.channel_capacity(), 128);
+    assert_eq!(buffer.left[5..105], sine[..]);
+    assert_eq!(buffer.right[0], 0.5);
+
+    let mut silent = StereoBuffer::new(16);
+    silent.append_slices(&[0.001; 16], &[0.0; 16]);
+    assert_eq!(silent.trim_silence(0.01, 8), 16);
+    assert_eq!(silent.cursor(), 0);
+}
+
+#[test]
+fn pre_emphasis_round_trip() {
+    let source = noise(1000, 3);
+    let mut buffer = MonoBuffer::new(1000);
+    buffer.append_slice(&source);
+
+    buffer.pre_emphasis(0.5);
+    assert_eq!(buffer[0], source[0]);
+    assert_eq!(buffer[1], source[1] - 0.5 * source[0]);
+
+    buffer.de_emphasis(0.5);
+    assert!(buffer
+        .iter()
+        .zip(&source)
+        .all(|(a, b)| (a - b).abs() < 1e-6));
+}
+
+#[test]
+fn apply_process_chain_in_order() {
+    let mut buffer = StereoBuffer::new(8);
+    buffer.append_slices(&a_test_vec(4), &b_test_vec(4));
+
+    ProcessChain::new()
+        .normalize(1.0)
+        .gain(2.0)
+        .clamp(1.0)
+        .fade_out(2)
+        .custom(|buffer| {
+            buffer.right.apply_gain(-1.0);
+        })
+        .apply(&mut buffer);
+
+    assert_eq!(buffer.left[..4], [0.5, 1.0, 0.5, 0.0]);
+    assert_eq!(buffer.right[..4], [-1.0, -1.0, -0.5, -0.0]);
+    assert_eq!(buffer.cursor(), 4);
+}
+
+#[test]
+fn fade_written_region() {
+    let mut buffer = StereoBuffer::new(8);
+    buffer.append_slices(&[1.0; 4], &[1.0; 4]);
+
+    buffer.fade_in(4);
+    assert_eq!(buffer.left[..4], [0.0, 0.25, 0.5, 0.75]);
+
+    buffer.fade_out(8);
+    assert_eq!(buffer.right[..4], [0.0, 0.125, 0.125, 0.0]);
+}
+
+#[test]
+fn compare_bit_exact() {
+    let mut a = StereoBuffer::new(10);
+    a.append_slices(&a_test_vec(6), &b_test_vec(6));
+    let mut b = StereoBuffer::new(20);
+    b.append_slices(&a_test_vec(6), &b_test_vec(6));
+
+    assert!(a.bit_exact_eq(&b));
+
+    b.right[5] += f32::EPSILON;
+    assert!(!a.bit_exact_eq(&b));
+
+    b.clear_cursor();
+    b.append_slices(&a_test_vec(5), &b_test_vec(5));
+    assert!(!a.bit_exact_eq(&b));
+
+    a.left[0] = f32::NAN;
+    assert!(!a.bit_exact_eq(&a));
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn mel_band_of_pure_tone() {
+    use audio_reader::buffer::spectrum::mel_band_edges;
+
+    let sample_rate = 44100;
+    let tone = 1000.0;
+    let samples: Vec<f32> = (0..sample_rate)
+        .map(|i| (2.0 * std::f32::consts::PI * tone * i as f32 / sample_rate as f32).sin())
+        .collect();
+    let mut buffer = MonoBuffer::new(samples.len());
+    buffer.append_slice(&samples);
+
+    let n_mels = 40;
+    let mel = buffer.mel_spectrogram(2048, 512, n_mels, sample_rate);
+    assert_eq!(mel.len(), (samples.len() - 2048) / 512 + 1);
+
+    let loudest = (0..n_mels)
+        .max_by(|a, b| {
+            let energy = |band: usize| mel.iter().map(|frame| frame[band]).sum::<f32>();
+            energy(*a).total_cmp(&energy(*b))
+        })
+        .unwrap();
+    let edges = mel_band_edges(n_mels, sample_rate);
+    assert!(edges[loudest] < tone && tone < edges[loudest + 2]);
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn spectrogram_without_frames() {
+    let mut buffer = MonoBuffer::new(4096);
+    buffer.append_slice(&vec![0.5; 4096]);
+
+    assert!(buffer.spectrogram(0, 512).is_empty());
+    assert!(buffer.spectrogram(2048, 0).is_empty());
+    assert!(buffer.mel_spectrogram(0, 512, 40, 44100).is_empty());
+}
+
+#[test]
+fn trim_to_exact_duration() {
+    let mut buffer = StereoBuffer::new(10);
+    buffer.append_slices(&a_test_vec(10), &b_test_vec(10));
+
+    buffer.trim_to_seconds(0.6, 10);
+    assert_eq!(buffer.cursor(), 6);
+    assert_eq!(buffer.left[..6], a_test_vec(6));
+
+    // 1.26 seconds at 10Hz round to 13 frames
+    buffer.trim_to_seconds(1.26, 10);
+    assert_eq!(buffer.cursor(), 13);
+    assert_eq!(buffer.left[..6], a_test_vec(6));
+    assert!(buffer.left[6..13].iter().all(|&x| x == 0f32));
+    assert!(buffer.right[6..13].iter().all(|&x| x == 0f32));
+}
+
+#[test]
+fn fix_inverted_polarity() {
+    let left = noise(1000, 4);
+    let inverted: Vec<f32> = left.iter().map(|x| -x).collect();
+    let mut buffer = StereoBuffer::new(1000);
+    buffer.append_slices(&left, &inverted);
+
+    assert!((buffer.correlation() + 1.0).abs() < 1e-6);
+    assert!(buffer.detect_polarity_inversion());
+
+    buffer.fix_polarity_inversion();
+    assert!((buffer.correlation() - 1.0).abs() < 1e-6);
+    assert_eq!(buffer.right[..1000], left[..]);
+
+    // Unrelated channels are wide, not inverted
+    let mut wide = StereoBuffer::new(1000);
+    wide.append_slices(&left, &noise(1000, 5));
+    assert!(!wide.detect_polarity_inversion());
+    wide.fix_polarity_inversion();
+    assert_eq!(wide.right[..1000], noise(1000, 5)[..]);
+}
+
+/// Deterministic white noise in [-1, 1]
+fn noise(len: usize, seed: u32) -> Vec<f32> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 8) as f32 / (1 << 23) as f32 - 1.0
+        })
+        .collect()
+}
+
+#[test]
+fn classify_speech_and_music() {
+    let sample_rate = 16000;
+    let seconds = 4;
+    let len = sample_rate * seconds;
+
+    let tone: Vec<f32> = (0..len)
+        .map(|i| (i as f32 / sample_rate as f32 * 440.0 * 2.0 * std::f32::consts::PI).sin())
+        .collect();
+    let mut music = StereoBuffer::new(len);
+    music.append_slices(&tone, &tone);
+    assert_eq!(
+        music.classify_content(sample_rate as u32),
+        ContentClass::Music
+    );
+
+    // Voiced syllables, unvoiced fricatives and pauses, 100ms each
+    let segment = sample_rate / 10;
+    let hiss = noise(len, 7);
+    let speech_like: Vec<f32> = (0..len)
+        .map(|i| match (i / segment) % 4 {
+            0 | 1 => {
+                0.5 * (i as f32 / sample_rate as f32 * 150.0 * 2.0 * std::f32::consts::PI).sin()
+            }
+            2 => 0.2 * hiss[i],
+            _ => 0.0,
+        })
+        .collect();
+    let mut speech = StereoBuffer::new(len);
+    speech.append_slices(&speech_like, &speech_like);
+    assert_eq!(
+        speech.classify_content(sample_rate as u32),
+        ContentClass::Speech
+    );
+
+    let mut silence = StereoBuffer::new(len);
+    silence.pad_silence();
+    assert_eq!(
+        silence.classify_content(sample_rate as u32),
+        ContentClass::Unknown
+    );
+}
+
+#[cfg(feature = "fft")]
+#[test]
+fn estimate_key_of_chord_progression() {
+    let sample_rate = 44100;
+    let tone =
+        |hz: f32, i: usize| (2.0 * std::f32::consts::PI * hz * i as f32 / sample_rate as f32).sin();
+    // C major, F major and G major triads, half a second each
+    let chords = [
+        [261.63, 329.63, 392.00],
+        [349.23, 440.00, 523.25],
+        [392.00, 493.88, 587.33],
+        [261.63, 329.63, 392.00],
+    ];
+    let samples: Vec<f32> = chords
+        .iter()
+        .flat_map(|chord| {
+            (0..sample_rate / 2)
+                .map(move |i| chord.iter().map(|hz| tone(*hz, i)).sum::<f32>() / 3.0)
+        })
+        .collect();
+    let mut buffer = StereoBuffer::new(samples.len());
+    buffer.append_slices(&samples, &samples);
+
+    let (key, confidence) = buffer.estimate_key(sample_rate as u32).unwrap();
+    assert_eq!(
+        key,
+        Key {
+            tonic: 0,
+            mode: Mode::Major
+        }
+    );
+    assert_eq!(key.to_string(), "C major");
+    assert!(confidence > 0.6 && confidence <= 1.0);
+
+    // Noise has no tonal center
+    let noise = noise(sample_rate * 2, 7);
+    let mut buffer = StereoBuffer::new(noise.len());
+    buffer.append_slices(&noise, &noise);
+    assert_eq!(buffer.estimate_key(sample_rate as u32), None);
+}
+
+#[test]
+fn apply_tremolo() {
+    let sample_rate = 100;
+    let mut buffer = StereoBuffer::new(200);
+    buffer.append_slices(&[1f32; 200], &[-1f32; 200]);
+
+    // One cycle every 50 frames
+    buffer.tremolo(2.0, 0.5, sample_rate);
+    let (left, right) = buffer.as_slice();
+
+    assert!((left[0] - 1.0).abs() < 1e-6);
+    assert!((left[25] - 0.5).abs() < 1e-6);
+    assert!((left[50] - 1.0).abs() < 1e-5);
+    assert!((left[175] - 0.5).abs() < 1e-5);
+    assert!(left.iter().all(|x| (0.5 - 1e-6..=1.0 + 1e-6).contains(x)));
+    assert!(left.iter().zip(right).all(|(l, r)| *l == -*r));
+}
+
+#[test]
+fn extract_side_channel() {
+    let mut buffer = StereoBuffer::new(8);
+    buffer.append_slices(&[1.0, 0.5, -0.5, 0.25], &[1.0, -0.5, 0.5, 0.75]);
+
+    let side = buffer.side_channel();
+    assert_eq!(side.cursor(), 4);
+    assert_eq!(side.slice(0, 4), &[0.0, 0.5, -0.5, -0.25]);
+
+    // The buffer is left untouched
+    assert_eq!(buffer.slice(0, 4).0, &[1.0, 0.5, -0.5, 0.25]);
+}
+
+#[test]
+fn snapshot_and_restore() {
+    let mut buffer = StereoBuffer::new(16);
+    buffer.append_slices(&[0.5, -0.5, 0.25], &[0.1, 0.2, 0.3]);
+
+    let snapshot = buffer.snapshot();
+    assert_eq!(snapshot.frames(), 3);
+
+    buffer.apply_gain(2.0);
+    buffer.append_slices(&[1.0], &[1.0]);
+    buffer.restore(snapshot);
+
+    assert_eq!(buffer.cursor(), 3);
+    assert_eq!(
+        buffer.slice(0, 3),
+        (&[0.5, -0.5, 0.25][..], &[0.1, 0.2, 0.3][..])
+    );
+    // Only the written region was kept
+    assert_eq!(buffer.channel_capacity(), 3);
+}
+
+#[test]
+fn downmix_surround_audio_buffer() {
+    use std::f32::consts::FRAC_1_SQRT_2;
+    use symphonia::core::audio::{AudioBuffer, Layout, Signal, SignalSpec};
+
+    let frames = 64;
+    let spec = SignalSpec::new_with_layout(44100, Layout::FivePointOne);
+    let mut surround = AudioBuffer::<f32>::new(frames as u64, spec);
+    surround.render_reserved(Some(frames));
+
+    // Front left, front right, centre, LFE, rear left, rear right
+    let levels = [0.5, 0.25, 0.4, 1.0, 0.2, 0.1];
+    for (i, level) in levels.iter().enumerate() {
+        surround.chan_mut(i).fill(*level);
+    }
+
+    let mut buffer = StereoBuffer::new(frames);
+    let mut overflow = StereoBuffer::new(0);
+    buffer.append_audio_buffer(&surround, &mut overflow);
+    assert_eq!(buffer.cursor(), frames);
+
+    // The LFE is dropped, the centre and surrounds are folded in at -3dB
+    let left = 0.5 + 0.4 * FRAC_1_SQRT_2 + 0.2 * FRAC_1_SQRT_2;
+    let right = 0.25 + 0.4 * FRAC_1_SQRT_2 + 0.1 * FRAC_1_SQRT_2;
+    let energy = |samples: &[f32]| samples.iter().map(|x| x * x).sum::<f32>();
+    let (left_samples, right_samples) = buffer.slice(0, frames);
+    assert!((energy(left_samples) - frames as f32 * left * left).abs() < 1e-3);
+    assert!((energy(right_samples) - frames as f32 * right * right).abs() < 1e-3);
+
+    // Read in film order the channels are left, centre, right, surrounds and LFE instead
+    let mut buffer = StereoBuffer::new(frames);
+    buffer.append_audio_buffer_mapped(&surround, 0, ChannelMap::Film5_1, &mut overflow);
+    let left = 0.5 + 0.25 * FRAC_1_SQRT_2 + 1.0 * FRAC_1_SQRT_2;
+    let right = 0.4 + 0.25 * FRAC_1_SQRT_2 + 0.2 * FRAC_1_SQRT_2;
+    let (left_samples, right_samples) = buffer.slice(0, frames);
+    assert!((energy(left_samples) - frames as f32 * left * left).abs() < 1e-3);
+    assert!((energy(right_samples) - frames as f32 * right * right).abs() < 1e-3);
+}
+
+#[test]
+fn iterate_mutably() {
+    let mut mono = MonoBuffer::new(4);
+    mono.append_slice(&[1.0, 2.0, 3.0, 4.0]);
+    mono.iter_mut().for_each(|sample| *sample *= 0.5);
+    assert_eq!(mono.as_slice(), &[0.5, 1.0, 1.5, 2.0]);
+
+    let mut stereo = StereoBuffer::new(3);
+    stereo.append_slices(&[1.0, 2.0, 3.0], &[-1.0, -2.0, -3.0]);
+    for (left, right) in stereo.iter_mut() {
+        std::mem::swap(left, right);
+    }
+    assert_eq!(
+        stereo.as_slice(),
+        (&[-1.0, -2.0, -3.0][..], &[1.0, 2.0, 3.0][..])
+    );
+}
+
+#[test]
+fn interleave_channels() {
+    let mut buffer = StereoBuffer::new(3);
+    buffer.append_slices(&[1.0, 2.0], &[-1.0, -2.0]);
+
+    // The whole capacity is interleaved, including the unwritten frame
+    let interleaved = buffer.to_interleaved();
+    assert_eq!(interleaved.len(), 6);
+    assert_eq!(interleaved[..4], [1.0, -1.0, 2.0, -2.0]);
+
+    let mut out = [9.0; 5];
+    assert_eq!(buffer.interleave_into(&mut out), 2);
+    assert_eq!(out, [1.0, -1.0, 2.0, -2.0, 9.0]);
+}
+
+#[test]
+fn interleave_written_region_for_ffi() {
+    let mut buffer = StereoBuffer::new(3);
+    buffer.append_slices(&[1.0, 2.0], &[-1.0, -2.0]);
+    assert_eq!(buffer.as_interleaved_vec(), vec![1.0, -1.0, 2.0, -2.0]);
+
+    let mut out = vec![9.0f32; 6];
+    let written = unsafe { buffer.fill_interleaved(out.as_mut_ptr(), 3) };
+    assert_eq!(written, 2);
+    assert_eq!(out, [1.0, -1.0, 2.0, -2.0, 9.0, 9.0]);
+
+    let written = unsafe { buffer.fill_interleaved(out.as_mut_ptr(), 1) };
+    assert_eq!(written, 1);
+    assert_eq!(
+        unsafe { buffer.fill_interleaved(std::ptr::null_mut(), 0) },
+        0
+    );
+}
+
+#[test]
+fn deinterleave_frames() {
+    let buffer = StereoBuffer::from_interleaved(&[1.0, -1.0, 2.0, -2.0, 3.0]);
+
+    // The orphan sample is dropped
+    assert_eq!(buffer.cursor(), 2);
+    assert_eq!(buffer.channel_capacity(), 2);
+    assert_eq!(buffer.slice(0, 2), (&[1.0, 2.0][..], &[-1.0, -2.0][..]));
+    assert_eq!(buffer.to_interleaved(), vec![1.0, -1.0, 2.0, -2.0]);
+}
+
+#[cfg(feature = "simd")]
+#[test]
+fn vectorized_conversion_matches_scalar() {
+    use audio_reader::buffer::simd::{s16_to_f32, s32_to_f32};
+    use symphonia::core::conv::IntoSample;
+
+    // Deterministic pseudo random input, with an odd length to exercise the scalar tail
+    let mut state = 0x2545_f491_u32;
+    let mut random = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        state
+    };
+    let s32_input: Vec<i32> = (0..1027).map(|_| random() as i32).collect();
+    let s16_input: Vec<i16> = s32_input.iter().map(|s| (*s >> 16) as i16).collect();
+
+    let mut converted = vec![0f32; s16_input.len()];
+    s16_to_f32(&s16_input, &mut converted);
+    for (sample, converted) in s16_input.iter().zip(&converted) {
+        let expected: f32 = (*sample).into_sample();
+        assert!(((expected - converted).abs() as f64) < 0.0001);
+    }
+
+    let mut converted = vec![0f32; s32_input.len()];
+    s32_to_f32(&s32_input, &mut converted);
+    for (sample, converted) in s32_input.iter().zip(&converted) {
+        let expected: f32 = (*sample).into_sample();
+        assert!(((expected - converted).abs() as f64) < 0.0001);
+    }
+
+    // Extremes of the range
+    let mut converted = [0f32; 8];
+    s16_to_f32(
+        &[i16::MIN, i16::MAX, 0, -1, 1, i16::MIN, i16::MAX, 0],
+        &mut converted,
+    );
+    assert_eq!(converted[0], -1.0);
+    assert_eq!(converted[1], i16::MAX as f32 / 32_768.0);
+}
+
+#[test]
+fn apply_gain_per_channel() {
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&[0.5, -0.25], &[0.5, -0.25]);
+
+    assert!(!buffer.apply_gain_lr(1.5, 0.5));
+    assert_eq!(
+        buffer.slice(0, 2),
+        (&[0.75, -0.375][..], &[0.25, -0.125][..])
+    );
+
+    // Nothing is clamped, the clipping is reported instead
+    assert!(buffer.apply_gain(2.0));
+    assert_eq!(buffer.slice(0, 2).0, &[1.5, -0.75]);
+
+    let mut mono = MonoBuffer::new(2);
+    mono.append_slice(&[0.5, -0.5]);
+    assert!(!mono.apply_gain(2.0));
+    assert!(mono.apply_gain(-1.5));
+}
+
+#[test]
+fn measure_rms_and_peak() {
+    let mut buffer = StereoBuffer::new(8);
+    buffer.append_slices(
+        &[0.5, -0.5, 1.0, -1.0, 0.0],
+        &[0.25, 0.25, 0.25, 0.25, -0.75],
+    );
+
+    // The unwritten tail is ignored
+    let (left_rms, right_rms) = buffer.rms();
+    assert!((left_rms - (2.5f32 / 5.0).sqrt()).abs() < 1e-6);
+    assert!((right_rms - (0.8125f32 / 5.0).sqrt()).abs() < 1e-6);
+    assert_eq!(buffer.peak(), (1.0, 0.75));
+
+    let (left, right) = buffer.rms_windowed(2);
+    assert_eq!(left, vec![0.5, 1.0, 0.0]);
+    assert_eq!(right, vec![0.25, 0.25, 0.75]);
+
+    let empty = MonoBuffer::new(4);
+    assert_eq!(empty.rms(), 0.0);
+    assert_eq!(empty.peak(), 0.0);
+    assert!(empty.rms_windowed(2).is_empty());
+}
+
+#[test]
+fn slice_within_bounds() {
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&[1.0, 2.0], &[3.0, 4.0]);
+
+    assert_eq!(
+        buffer.try_slice(0, 2),
+        Ok((&[1.0, 2.0][..], &[3.0, 4.0][..]))
+    );
+    assert_eq!(
+        buffer.try_slice(2, 3),
+        Err(BufferError::OutOfBounds {
+            start: 2,
+            len: 3,
+            capacity: 4
+        })
+    );
+    assert!(buffer.left.try_slice(usize::MAX, 2).is_err());
+}
+
+#[test]
+fn append_planar_channels() {
+    let a = a_test_vec(3);
+    let b = b_test_vec(3);
+    let c = vec![0.5; 3];
+    let mut buffer = StereoBuffer::new(6);
+    let mut overflow = StereoBuffer::new(3);
+
+    buffer.append_planar(&[&a], ChannelMap::Identity, &mut overflow);
+    assert_eq!(buffer.slice(0, 3), (&a[..], &a[..]));
+
+    buffer.append_planar(&[&a, &b, &c], ChannelMap::SwapLR, &mut overflow);
+    assert_eq!(buffer.slice(3, 3), (&b[..], &a[..]));
+
+    // Frames past the capacity spill into the overflow
+    buffer.append_planar(&[&a, &b], ChannelMap::Identity, &mut overflow);
+    assert_eq!(buffer.cursor(), 6);
+    assert_eq!(overflow.slice(0, 3), (&a[..], &b[..]));
+}
+
+#[test]
+fn downmix_planar_surround_presets() {
+    let level = |value: f32| vec![value; 4];
+    let (l, r, c, lfe, ls, rs) = (
+        level(0.5),
+        level(0.25),
+        level(0.4),
+        level(1.0),
+        level(0.2),
+        level(0.1),
+    );
+    let gain = std::f32::consts::FRAC_1_SQRT_2;
+    let mut overflow = StereoBuffer::new(0);
+
+    // The same channels downmix alike in either order once mapped
+    let mut smpte = StereoBuffer::new(4);
+    let planes = [&l[..], &r[..], &c[..], &lfe[..], &ls[..], &rs[..]];
+    smpte.append_planar(&planes, ChannelMap::Smpte5_1, &mut overflow);
+    let mut film = StereoBuffer::new(4);
+    let planes = [&l[..], &c[..], &r[..], &ls[..], &rs[..], &lfe[..]];
+    film.append_planar(&planes, ChannelMap::Film5_1, &mut overflow);
+
+    let left = 0.5 + (0.4 + 0.2) * gain;
+    let right = 0.25 + (0.4 + 0.1) * gain;
+    for buffer in [&smpte, &film] {
+        let (left_samples, right_samples) = buffer.slice(0, 4);
+        assert!(left_samples.iter().all(|s| (s - left).abs() < 1e-6));
+        assert!(right_samples.iter().all(|s| (s - right).abs() < 1e-6));
+    }
+
+    // Other channel counts are read as with the identity map
+    let mut stereo = StereoBuffer::new(4);
+    stereo.append_planar(&[&l, &r], ChannelMap::Film5_1, &mut overflow);
+    assert_eq!(stereo.slice(0, 4), (&l[..], &r[..]));
+}
+
+#[test]
+fn access_channels_by_index() {
+    let a = a_test_vec(4);
+    let b = b_test_vec(4);
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&a, &b);
+
+    assert_eq!(buffer.channel(Channel::Left).slice(0, 4), &a[..]);
+    assert_eq!(buffer.channel_slice(Channel::Right, 1, 2), &b[1..3]);
+
+    buffer.channel_mut(Channel::Right).slice_mut(0, 4).fill(0.0);
+    assert_eq!(buffer.channel_slice(Channel::Left, 0, 4), &a[..]);
+    assert_eq!(buffer.channel_slice(Channel::Right, 0, 4), &[0.0; 4]);
+}
+
+#[test]
+fn remove_dc_offset() {
+    // Whole periods of a sine average out, leaving only the constant
+    let sine: Vec<f32> = (0..4800)
+        .map(|i| (i as f32 * 2.0 * std::f32::consts::PI / 480.0).sin() * 0.5)
+        .collect();
+    let biased = |bias: f32| sine.iter().map(|s| s + bias).collect::<Vec<_>>();
+    let mut buffer = StereoBuffer::new(sine.len());
+    buffer.append_slices(&biased(0.2), &biased(-0.1));
+
+    let (left, right) = buffer.dc_offset();
+    assert!((left - 0.2).abs() < 1e-4);
+    assert!((right + 0.1).abs() < 1e-4);
+
+    buffer.remove_dc();
+    let (left, right) = buffer.dc_offset();
+    assert!(left.abs() < 1e-6 && right.abs() < 1e-6);
+    assert!((buffer.left.slice(120, 1)[0] - 0.5).abs() < 1e-4);
+
+    assert_eq!(MonoBuffer::new(4).dc_offset(), 0.0);
+}
+
+#[test]
+fn write_and_read_wrapping_ring() {
+    let a = a_test_vec(6);
+    let mut ring = MonoBuffer::new(4);
+    let mut output = [0.0; 4];
+
+    // One sample is kept free
+    assert_eq!(ring.ring_space(), 3);
+    assert_eq!(ring.write_wrapping(&a), 3);
+    assert_eq!(ring.unread(), 3);
+    assert_eq!(ring.write_wrapping(&a[3..]), 0);
+
+    assert_eq!(ring.read_wrapping(&mut output[..2]), 2);
+    assert_eq!(output[..2], a[..2]);
+    assert_eq!(ring.read_cursor(), 2);
+
+    // The freed front is reused
+    assert_eq!(ring.write_wrapping(&a[3..]), 2);
+    assert_eq!(ring.write_cursor(), 1);
+    assert_eq!(ring.unread(), 3);
+
+    assert_eq!(ring.read_wrapping(&mut output), 3);
+    assert_eq!(output[..3], a[2..5]);
+    assert_eq!(ring.unread(), 0);
+
+    let mut stereo = StereoBuffer::new(4);
+    let b = b_test_vec(6);
+    let (mut left, mut right) = ([0.0; 2], [0.0; 2]);
+    assert_eq!(stereo.write_wrapping(&a[..3], &b[..3]), 3);
+    assert_eq!(stereo.read_wrapping(&mut left, &mut right), 2);
+    assert_eq!(stereo.write_wrapping(&a[3..], &b[3..]), 2);
+    assert_eq!(stereo.unread(), 3);
+    assert_eq!(stereo.read_wrapping(&mut left, &mut right), 2);
+    assert_eq!((left, right), ([a[2], a[3]], [b[2], b[3]]));
+}
+
+#[test]
+fn fill_channels_independently() {
+    let mut buffer = StereoBuffer::new(4);
+    // The right channel starts late
+    buffer.fill_channel(Channel::Left, &[1.0, 2.0, 3.0]);
+    buffer.fill_channel(Channel::Right, &[4.0]);
+    assert_eq!(buffer.left.cursor(), 3);
+    assert_eq!(buffer.right.cursor(), 1);
+
+    assert_eq!(buffer.sync_cursors(), 3);
+    assert_eq!(
+        buffer.slice(0, 3),
+        (&[1.0, 2.0, 3.0][..], &[4.0, 0.0, 0.0][..])
+    );
+
+    // Strict methods work again once synced
+    buffer.append_slices(&[5.0], &[6.0]);
+    assert_eq!(buffer.cursor(), 4);
+    assert_eq!(buffer.sync_cursors(), 4);
+}
+
+#[test]
+fn mix_down_to_mono() {
+    let mut buffer = StereoBuffer::new(4);
+    buffer.append_slices(&[0.5, 1.0, -0.25], &[0.25, 0.5, 0.25]);
+
+    let mono = buffer.to_mono(MonoMix::Average);
+    assert_eq!(mono.cursor(), 3);
+    assert_eq!(mono.slice(0, 3), &[0.375, 0.75, 0.0]);
+    assert_eq!(buffer.to_mono(MonoMix::Sum).slice(0, 3), &[0.75, 1.5, 0.0]);
+    assert_eq!(
+        buffer.to_mono(MonoMix::Left).slice(0, 3),
+        &[0.5, 1.0, -0.25]
+    );
+    assert_eq!(
+        buffer.to_mono(MonoMix::Right).slice(0, 3),
+        &[0.25, 0.5, 0.25]
+    );
+
+    // Only the written region is mixed
+    assert_eq!(buffer.to_mono(MonoMix::Average).channel_capacity(), 3);
+}
@@ -1,14 +1,35 @@
+#![allow(dead_code)]
+
 use audio_reader::prelude::{Buffer, StereoBuffer};
 
 use hound::WavSpec;
 use std::f32::consts::PI;
 use std::{path::PathBuf, process::Command};
+use symphonia::core::{
+    checksum::{Crc16Ansi, Crc8Ccitt, Md5},
+    io::Monitor,
+};
 
 pub const ACCEPTABLE_FLOAT_ERROR: f64 = 0.0001; // Used when converting between different bit sized samples
 pub const ACCEPTABLE_ERROR: f64 = 0.000000000001; // Used when expecting identical values
 
 pub const INT_MONO_SINE: &str = "assets/int_mono_sine.wav";
 pub const FLOAT_STEREO_SINE: &str = "assets/float_stereo_sine.wav";
+pub const TAGGED_STEREO_SINE: &str = "assets/tagged_stereo_sine.wav";
+pub const ID3_MONO_SINE: &str = "assets/id3_mono_sine.wav";
+pub const LEFT_ONLY_SINE: &str = "assets/left_only_sine.wav";
+pub const EMPTY_STEREO: &str = "assets/empty_stereo.wav";
+pub const CLIPPED_STEREO: &str = "assets/clipped_stereo.wav";
+pub const CLIPPED_MONO: &str = "assets/clipped_mono.wav";
+pub const FLAC_MONO_SINE: &str = "assets/flac_mono_sine.flac";
+pub const CORRUPTED_FLAC_MONO_SINE: &str = "assets/corrupted_flac_mono_sine.flac";
+pub const MISCOUNTED_FLAC_MONO_SINE: &str = "assets/miscounted_flac_mono_sine.flac";
+pub const TAIL_MISCOUNTED_FLAC_MONO_SINE: &str = "assets/tail_miscounted_flac_mono_sine.flac";
+pub const UNCOUNTED_FLAC_MONO_SINE: &str = "assets/uncounted_flac_mono_sine.flac";
+pub const MKV_FLAC_MONO_SINE: &str = "assets/mkv_flac_mono_sine.mkv";
+
+pub const SINE_TITLE: &str = "A4 Sine";
+pub const SINE_ARTIST: &str = "ToneGen";
 
 pub const SAMPLE_RATE: u32 = 44100; // will be used as a buffer size too (1second buffers)
 pub const HOST_BUFFER_SIZE: usize = 1024;
@@ -20,6 +41,13 @@ pub const MONO_INT: WavSpec = WavSpec {
     sample_format: hound::SampleFormat::Int,
 };
 
+pub const MONO_FLOAT: WavSpec = WavSpec {
+    channels: 1,
+    sample_rate: SAMPLE_RATE,
+    bits_per_sample: 32,
+    sample_format: hound::SampleFormat::Float,
+};
+
 pub const STEREO_FLOAT: WavSpec = WavSpec {
     channels: 2,
     sample_rate: SAMPLE_RATE,
@@ -86,6 +114,277 @@ pub fn stereo_float_sine() {
     writer.finalize().unwrap();
 }
 
+/// Generate a sine wave in the left channel and silence in the right one (stereo & 32bits)
+pub fn left_only_sine() {
+    let mut writer = hound::WavWriter::create(LEFT_ONLY_SINE, STEREO_FLOAT).unwrap();
+
+    for t in sine_float_samples() {
+        writer.write_sample(t).unwrap();
+        writer.write_sample(0f32).unwrap();
+    }
+
+    writer.finalize().unwrap();
+}
+
+/// Generate a sine wave amplified past full scale in the left channel and silence in the right one (stereo & 32bits)
+pub fn clipped_stereo(gain: f32) {
+    let mut writer = hound::WavWriter::create(CLIPPED_STEREO, STEREO_FLOAT).unwrap();
+
+    for t in sine_float_samples() {
+        writer.write_sample(t * gain).unwrap();
+        writer.write_sample(0f32).unwrap();
+    }
+
+    writer.finalize().unwrap();
+}
+
+/// Generate a sine wave amplified by `gain` (mono & 32bits), clipping when above 1
+pub fn clipped_mono(gain: f32) {
+    let mut writer = hound::WavWriter::create(CLIPPED_MONO, MONO_FLOAT).unwrap();
+
+    for t in sine_float_samples() {
+        writer.write_sample(t * gain).unwrap();
+    }
+
+    writer.finalize().unwrap();
+}
+
+/// Write a stereo file (32bits) without any frames
+pub fn empty_stereo() {
+    let writer = hound::WavWriter::create(EMPTY_STEREO, STEREO_FLOAT).unwrap();
+    writer.finalize().unwrap();
+}
+
+/// Encode a RIFF chunk, padded to an even size
+fn riff_chunk(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut chunk = id.to_vec();
+    chunk.extend((data.len() as u32).to_le_bytes());
+    chunk.extend(data);
+    if data.len() % 2 == 1 {
+        chunk.push(0);
+    }
+    chunk
+}
+
+/// Generate a sine wave (stereo & 16bits) tagged with a RIFF INFO chunk and write it to a file
+pub fn tagged_stereo_sine() {
+    let mut fmt = Vec::new();
+    fmt.extend(1u16.to_le_bytes()); // PCM
+    fmt.extend(2u16.to_le_bytes()); // channels
+    fmt.extend(SAMPLE_RATE.to_le_bytes());
+    fmt.extend((SAMPLE_RATE * 4).to_le_bytes()); // byte rate
+    fmt.extend(4u16.to_le_bytes()); // block align
+    fmt.extend(16u16.to_le_bytes()); // bits per sample
+
+    let mut info = b"INFO".to_vec();
+    info.extend(riff_chunk(b"INAM", format!("{}\0", SINE_TITLE).as_bytes()));
+    info.extend(riff_chunk(b"IART", format!("{}\0", SINE_ARTIST).as_bytes()));
+
+    let mut data = Vec::new();
+    for t in sine_int_samples() {
+        data.extend(t.to_le_bytes());
+        data.extend(t.to_le_bytes());
+    }
+
+    let mut wave = b"WAVE".to_vec();
+    wave.extend(riff_chunk(b"fmt ", &fmt));
+    wave.extend(riff_chunk(b"LIST", &info));
+    wave.extend(riff_chunk(b"data", &data));
+
+    std::fs::write(TAGGED_STEREO_SINE, riff_chunk(b"RIFF", &wave)).unwrap();
+}
+
+/// Encode an ID3v2.4 synchsafe integer
+fn synchsafe(n: u32) -> [u8; 4] {
+    [
+        (n >> 21) as u8 & 0x7f,
+        (n >> 14) as u8 & 0x7f,
+        (n >> 7) as u8 & 0x7f,
+        n as u8 & 0x7f,
+    ]
+}
+
+/// Encode an ID3v2.4 frame
+fn id3v2_frame(id: &[u8; 4], data: &[u8]) -> Vec<u8> {
+    let mut frame = id.to_vec();
+    frame.extend(synchsafe(data.len() as u32));
+    frame.extend([0u8, 0u8]); // flags
+    frame.extend(data);
+    frame
+}
+
+/// Prepend an ID3v2.4 tag holding a title and an embedded picture of `picture_len` bytes to a file
+pub fn prepend_id3v2_tag(input: &str, output: &str, picture_len: usize) {
+    let mut title = vec![3u8]; // UTF-8
+    title.extend(SINE_TITLE.as_bytes());
+
+    let mut picture = vec![0u8]; // ISO-8859-1
+    picture.extend(b"image/png\0");
+    picture.push(3); // front cover
+    picture.push(0); // empty description
+    picture.extend(vec![0xaau8; picture_len]);
+
+    let mut frames = id3v2_frame(b"TIT2", &title);
+    frames.extend(id3v2_frame(b"APIC", &picture));
+
+    let mut tagged = b"ID3".to_vec();
+    tagged.extend([4u8, 0u8, 0u8]); // version 2.4.0, no flags
+    tagged.extend(synchsafe(frames.len() as u32));
+    tagged.extend(frames);
+    tagged.extend(std::fs::read(input).unwrap());
+
+    std::fs::write(output, tagged).unwrap();
+}
+
+/// Frames per FLAC frame written by `flac_mono_sine`
+pub const FLAC_BLOCK_SIZE: usize = 4096;
+
+/// Encode a FLAC frame of 16 bits mono samples, stored verbatim
+fn flac_frame(number: u8, samples: &[i16]) -> Vec<u8> {
+    let mut frame = vec![0xff, 0xf8]; // sync code, fixed blocking
+    frame.push(0x79); // block size in the header, 44.1kHz
+    frame.push(0x08); // mono, 16 bits per sample
+    frame.push(number); // UTF-8 coded frame number, single byte under 128
+    frame.extend((samples.len() as u16 - 1).to_be_bytes());
+    let mut crc8 = Crc8Ccitt::new(0);
+    crc8.process_buf_bytes(&frame);
+    frame.push(crc8.crc());
+
+    frame.push(0x02); // verbatim subframe
+    for sample in samples {
+        frame.extend(sample.to_be_bytes());
+    }
+
+    let mut crc16 = Crc16Ansi::new(0);
+    crc16.process_buf_bytes(&frame);
+    frame.extend(crc16.crc().to_be_bytes());
+    frame
+}
+
+/// Generate a sine wave (mono & 16bits), encode it as FLAC and write it to a file
+///
+/// When `corrupted`, one sample is altered after the checksum of the content is computed.
+pub fn flac_mono_sine(output: &str, corrupted: bool) {
+    flac_mono_sine_stating(output, corrupted, None);
+}
+
+/// Same as `flac_mono_sine`, with the frame count stated in the STREAMINFO block overridden
+pub fn flac_mono_sine_stating(output: &str, corrupted: bool, stated_frames: Option<usize>) {
+    let mut samples = sine_int_samples();
+    let stated_frames = stated_frames.unwrap_or(samples.len());
+
+    let mut md5 = Md5::default();
+    for sample in &samples {
+        md5.process_buf_bytes(&sample.to_le_bytes());
+    }
+    if corrupted {
+        samples[SAMPLE_RATE as usize / 2] ^= 0x0ff0;
+    }
+
+    let mut flac = flac_stream_header(FLAC_BLOCK_SIZE, stated_frames, md5.md5());
+    for (number, block) in samples.chunks(FLAC_BLOCK_SIZE).enumerate() {
+        flac.extend(flac_frame(number as u8, block));
+    }
+
+    std::fs::write(output, flac).unwrap();
+}
+
+/// The stream marker and STREAMINFO block of a mono 16 bits FLAC stream
+fn flac_stream_header(block_size: usize, stated_frames: usize, md5: [u8; 16]) -> Vec<u8> {
+    let mut stream_info = Vec::new();
+    stream_info.extend((block_size as u16).to_be_bytes()); // min block size
+    stream_info.extend((block_size as u16).to_be_bytes()); // max block size
+    stream_info.extend([0u8; 6]); // unknown min and max frame sizes
+                                  // 20 bits sample rate, 3 bits channels - 1, 5 bits bits per sample - 1, 36 bits total samples
+    let packed = (SAMPLE_RATE as u64) << 44 | 15 << 36 | stated_frames as u64;
+    stream_info.extend(packed.to_be_bytes());
+    stream_info.extend(md5);
+
+    let mut flac = b"fLaC".to_vec();
+    flac.push(0x80); // last metadata block, STREAMINFO
+    flac.extend(&(stream_info.len() as u32).to_be_bytes()[1..]);
+    flac.extend(stream_info);
+    flac
+}
+
+/// Frames per block of `mkv_flac_mono_sine`, 10ms at 44.1kHz
+pub const MKV_BLOCK_SIZE: usize = 441;
+
+/// Encode an EBML element, its size always being coded on 8 bytes
+fn ebml_element(id: u32, data: &[u8]) -> Vec<u8> {
+    let id_bytes = id.to_be_bytes();
+    let leading = id_bytes.iter().take_while(|byte| **byte == 0).count();
+    let mut element = id_bytes[leading..].to_vec();
+    element.push(0x01);
+    element.extend(&(data.len() as u64).to_be_bytes()[1..]);
+    element.extend(data);
+    element
+}
+
+/// Generate a sine wave (mono & 16bits), encode it as FLAC and store it in a Matroska container
+///
+/// Matroska timestamps are in milliseconds, unlike the sample rate based time base of most containers.
+/// Each 10ms block is stored in its own cluster, and no cues are written.
+pub fn mkv_flac_mono_sine(output: &str) {
+    let samples = sine_int_samples();
+    let block_ms = (MKV_BLOCK_SIZE * 1000 / SAMPLE_RATE as usize) as u64;
+
+    let ebml = [
+        ebml_element(0x4282, b"matroska"),         // DocType
+        ebml_element(0x4287, &4u64.to_be_bytes()), // DocTypeVersion
+    ]
+    .concat();
+    let info = ebml_element(0x2AD7B1, &1_000_000u64.to_be_bytes()); // TimestampScale, 1ms
+
+    let audio = [
+        ebml_element(0xB5, &(SAMPLE_RATE as f64).to_be_bytes()), // SamplingFrequency
+        ebml_element(0x9F, &1u64.to_be_bytes()),                 // Channels
+        ebml_element(0x6264, &16u64.to_be_bytes()),              // BitDepth
+    ]
+    .concat();
+    let track = [
+        ebml_element(0xD7, &1u64.to_be_bytes()),   // TrackNumber
+        ebml_element(0x73C5, &1u64.to_be_bytes()), // TrackUID
+        ebml_element(0x83, &2u64.to_be_bytes()),   // TrackType, audio
+        ebml_element(0x86, b"A_FLAC"),             // CodecID
+        ebml_element(
+            0x63A2,
+            &flac_stream_header(MKV_BLOCK_SIZE, samples.len(), [0; 16]),
+        ),
+        ebml_element(0x23E383, &(block_ms * 1_000_000).to_be_bytes()), // DefaultDuration, ns
+        ebml_element(0xE1, &audio),
+    ]
+    .concat();
+    let tracks = ebml_element(0xAE, &track); // TrackEntry
+
+    let mut segment = [
+        ebml_element(0x1549A966, &info),   // Info
+        ebml_element(0x1654AE6B, &tracks), // Tracks
+    ]
+    .concat();
+    for (number, block) in samples.chunks(MKV_BLOCK_SIZE).enumerate() {
+        // Track 1, no relative timestamp, keyframe without lacing
+        let simple_block = [
+            &[0x81, 0x00, 0x00, 0x80][..],
+            &flac_frame(number as u8, block),
+        ]
+        .concat();
+        let cluster = [
+            ebml_element(0xE7, &(number as u64 * block_ms).to_be_bytes()), // Timestamp
+            ebml_element(0xA3, &simple_block),                             // SimpleBlock
+        ]
+        .concat();
+        segment.extend(ebml_element(0x1F43B675, &cluster)); // Cluster
+    }
+
+    let mkv = [
+        ebml_element(0x1A45DFA3, &ebml),    // EBML
+        ebml_element(0x18538067, &segment), // Segment
+    ]
+    .concat();
+    std::fs::write(output, mkv).unwrap();
+}
+
 /// Get the euclidean distance between paired samples
 pub fn channel_error(left: &[f32], right: &[f32]) -> Vec<f64> {
     left.iter()
@@ -4,13 +4,15 @@ use audio_reader::prelude::*;
 use common::*;
 
 use std::i16;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+use symphonia::core::{audio::Layout, units::Time};
 
 /// Get a SyncFullReader for a given file
 fn default_reader(path: PathBuf) -> SyncFullReader {
     SyncFullReader::new(
         path,
         HOST_BUFFER_SIZE,
+        UpmixPolicy::DuplicateMono,
         Default::default(),
         Default::default(),
         Default::default(),
@@ -64,6 +66,43 @@ fn read_mono_int_wav() {
     );
 }
 
+#[test]
+fn upmix_mono_sources() {
+    mono_int_sine();
+    let pregen_sine = sine_float_samples();
+    let silence = vec![0f32; pregen_sine.len()];
+    let mono_reader = |upmix: UpmixPolicy| {
+        SyncFullReader::new(
+            PathBuf::from(INT_MONO_SINE),
+            HOST_BUFFER_SIZE,
+            upmix,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+    };
+
+    let mut reader = mono_reader(UpmixPolicy::LeftOnly).unwrap();
+    assert!(matches!(reader.source_layout, Layout::Mono));
+    reader.read_sync().unwrap();
+    assert_integrity(
+        &pregen_sine,
+        &silence,
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+
+    assert!(matches!(
+        mono_reader(UpmixPolicy::Error),
+        Err(SampleLoadError::UnsupportedChannelLayout(Layout::Mono))
+    ));
+
+    // Real stereo is left untouched
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert!(matches!(reader.source_layout, Layout::Stereo));
+}
+
 /// Tests reading capabilities against an external reader
 #[test]
 fn read_stereo_float_wav() {
@@ -86,13 +125,138 @@ fn read_stereo_float_wav() {
 
     reader.read_sync().unwrap();
     assert_eq!(reader.buffer.capacity() % HOST_BUFFER_SIZE, 0);
+    assert!(reader.warnings().is_empty());
 
     assert_integrity(&pregen_sine, &pregen_sine, &reader.buffer, ACCEPTABLE_ERROR);
 }
 
+#[test]
+fn read_from_custom_media_source() {
+    stereo_float_sine();
+    let file = std::fs::File::open(FLOAT_STEREO_SINE).unwrap();
+
+    let mut reader = SyncFullReader::from_media_source(
+        Box::new(file),
+        Some("wav"),
+        HOST_BUFFER_SIZE,
+        UpmixPolicy::DuplicateMono,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+
+    reader.read_sync().unwrap();
+    let pregen_sine = sine_float_samples();
+    assert_integrity(&pregen_sine, &pregen_sine, &reader.buffer, ACCEPTABLE_ERROR);
+}
+
+#[test]
+fn read_time_range() {
+    stereo_float_sine();
+    let pregen_sine = sine_float_samples();
+    let range_reader = |start: f64, end: f64| {
+        let mut reader = SyncFullReader::new_range(
+            PathBuf::from(FLOAT_STEREO_SINE),
+            HOST_BUFFER_SIZE,
+            UpmixPolicy::DuplicateMono,
+            Duration::from_secs_f64(start),
+            Duration::from_secs_f64(end),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        reader.read_sync().unwrap();
+        reader
+    };
+
+    let reader = range_reader(0.25, 0.5);
+    let target = &pregen_sine[11_025..22_050];
+    assert_eq!(reader.buffer.capacity() % HOST_BUFFER_SIZE, 0);
+    assert_integrity(target, target, &reader.buffer, ACCEPTABLE_ERROR);
+
+    // Stops at the end of the content
+    let reader = range_reader(0.75, 5.0);
+    let target = &pregen_sine[33_075..];
+    assert_integrity(target, target, &reader.buffer, ACCEPTABLE_ERROR);
+
+    // Empty ranges yield an empty buffer
+    let mut reader = range_reader(0.5, 0.5);
+    assert_eq!(reader.buffer.capacity(), 0);
+    assert!(reader.next_slice().unwrap().0.is_empty());
+}
+
+#[test]
+fn detect_clipping() {
+    let gain = 1.5;
+    clipped_stereo(gain);
+    let pregen_sine = sine_float_samples();
+    let expected = pregen_sine
+        .iter()
+        .filter(|s| (*s * gain).abs() > 1.0)
+        .count();
+    let first = pregen_sine.iter().position(|s| (*s * gain).abs() > 1.0);
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(CLIPPED_STEREO), HOST_BUFFER_SIZE)
+        .detect_clipping(true)
+        .build()
+        .unwrap();
+    reader.read_sync().unwrap();
+    assert!(expected > 0);
+    assert_eq!(reader.clipped_sample_count(), expected);
+    assert_eq!(
+        reader.first_clipped_position(),
+        first.map(|frame| frame as u64)
+    );
+
+    // Opt-in only
+    let mut reader = default_reader(PathBuf::from(CLIPPED_STEREO));
+    reader.read_sync().unwrap();
+    assert_eq!(reader.clipped_sample_count(), 0);
+    assert_eq!(reader.first_clipped_position(), None);
+}
+
+#[test]
+fn detect_clipping_after_upmix() {
+    let gain = 1.5;
+    clipped_mono(gain);
+    let clipped = sine_float_samples()
+        .iter()
+        .filter(|s| (*s * gain).abs() > 1.0)
+        .count();
+    let clipped_reader = |upmix: UpmixPolicy| {
+        let mut reader = SyncFullReader::builder(PathBuf::from(CLIPPED_MONO), HOST_BUFFER_SIZE)
+            .upmix_policy(upmix)
+            .detect_clipping(true)
+            .build()
+            .unwrap();
+        reader.read_sync().unwrap();
+        reader.clipped_sample_count()
+    };
+
+    // Samples are counted in the channels they are read into
+    assert!(clipped > 0);
+    assert_eq!(clipped_reader(UpmixPolicy::DuplicateMono), 2 * clipped);
+    assert_eq!(clipped_reader(UpmixPolicy::LeftOnly), clipped);
+}
+
+#[test]
+fn consume_empty_source() {
+    empty_stereo();
+    let mut reader = default_reader(PathBuf::from(EMPTY_STEREO));
+    reader.read_sync().unwrap();
+
+    assert_eq!(reader.buffer.capacity(), 0);
+    assert_eq!(reader.percentage_consumed(), 1.0);
+    assert!(reader.needs_refill(0.5));
+
+    reader.next_slice().unwrap();
+    assert!(reader.percentage_consumed().is_finite());
+}
+
 #[test]
 fn read_stereo_mp3() {
-    // TODO fails because of padding and delay which is not handled yet
     read_other_format("mp3", ACCEPTABLE_FLOAT_ERROR);
 }
 
@@ -106,3 +270,643 @@ fn read_stereo_ogg() {
     // TODO fails because of delay which is not handled yet
     read_other_format("ogg", ACCEPTABLE_FLOAT_ERROR);
 }
+
+#[test]
+fn reject_unexpected_sample_rate() {
+    stereo_float_sine();
+    let builder = || SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE);
+
+    let error = builder().expected_sample_rate(48000).build().err().unwrap();
+    assert!(matches!(
+        error,
+        SampleLoadError::SampleRateMismatch {
+            expected: 48000,
+            actual: SAMPLE_RATE
+        }
+    ));
+    assert_eq!(
+        error.to_string(),
+        "Expected a sample rate of 48000Hz, found 44100Hz"
+    );
+
+    assert!(builder().expected_sample_rate(SAMPLE_RATE).build().is_ok());
+}
+
+#[test]
+fn issue_chunks_while_reading() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+
+    let mut chunks = 0;
+    let (mut left, mut right) = (Vec::new(), Vec::new());
+    reader
+        .read_sync_with(|chunk_left, chunk_right| {
+            chunks += 1;
+            left.extend_from_slice(chunk_left);
+            right.extend_from_slice(chunk_right);
+        })
+        .unwrap();
+
+    assert!(chunks > 1);
+    assert_eq!(left.len(), SAMPLE_RATE as usize);
+    assert_eq!(reader.buffer.slice(0, left.len()), (&left[..], &right[..]));
+}
+
+#[test]
+fn read_without_alignment() {
+    stereo_float_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .align(false)
+        .build()
+        .unwrap();
+
+    reader.read_sync().unwrap();
+    assert_eq!(reader.buffer.channel_capacity(), SAMPLE_RATE as usize);
+
+    let full_slices = SAMPLE_RATE as usize / HOST_BUFFER_SIZE;
+    for _ in 0..full_slices {
+        let (left, right) = reader.next_slice().unwrap();
+        assert_eq!(left.len(), HOST_BUFFER_SIZE);
+        assert_eq!(right.len(), HOST_BUFFER_SIZE);
+    }
+
+    let (left, _) = reader.next_slice().unwrap();
+    assert_eq!(left.len(), SAMPLE_RATE as usize % HOST_BUFFER_SIZE);
+
+    let (left, _) = reader.next_slice().unwrap();
+    assert_eq!(left.len(), HOST_BUFFER_SIZE);
+}
+
+#[test]
+fn grow_according_to_policy() {
+    assert_eq!(GrowthPolicy::Double.additional(4096), 4096);
+    assert_eq!(GrowthPolicy::Factor(1.5).additional(4096), 2048);
+    assert_eq!(GrowthPolicy::Factor(0.5).additional(4096), 0);
+    assert_eq!(GrowthPolicy::FixedChunks(1024).additional(4096), 1024);
+
+    stereo_float_sine();
+    let pregen_sine = sine_float_samples();
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .growth_policy(GrowthPolicy::FixedChunks(HOST_BUFFER_SIZE))
+        .build()
+        .unwrap();
+    reader.read_sync().unwrap();
+    assert_integrity(&pregen_sine, &pregen_sine, &reader.buffer, ACCEPTABLE_ERROR);
+}
+
+#[test]
+fn report_remaining_samples() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+
+    let capacity = reader.buffer.channel_capacity();
+    assert_eq!(reader.remaining_samples(), capacity);
+    for issued in 1..capacity / HOST_BUFFER_SIZE {
+        reader.next_slice().unwrap();
+        assert_eq!(
+            reader.remaining_samples(),
+            capacity - issued * HOST_BUFFER_SIZE
+        );
+    }
+
+    // The last slice before wrapping around
+    assert_eq!(reader.remaining_samples(), HOST_BUFFER_SIZE);
+    reader.next_slice().unwrap();
+    assert_eq!(reader.remaining_samples(), capacity);
+}
+
+#[test]
+fn crossfade_loop_seam() {
+    stereo_float_sine();
+    // Largest step between two samples of the sine itself
+    let sine_delta = 2.0 * std::f32::consts::PI * 440.0 / SAMPLE_RATE as f32;
+    // Ends mid-period, far from the starting phase
+    let looped_reader = || {
+        let mut reader = SyncFullReader::new_range(
+            PathBuf::from(FLOAT_STEREO_SINE),
+            HOST_BUFFER_SIZE,
+            UpmixPolicy::DuplicateMono,
+            Duration::ZERO,
+            Duration::from_secs_f64(0.2506),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        reader.read_sync().unwrap();
+        reader
+    };
+    let seam_delta = |reader: &SyncFullReader, len: usize| {
+        let left = &reader.buffer.left[..len];
+        let wrap = (left[0] - left[left.len() - 1]).abs();
+        left.windows(2)
+            .map(|pair| (pair[1] - pair[0]).abs())
+            .fold(wrap, f32::max)
+    };
+
+    let reader = looped_reader();
+    assert!(seam_delta(&reader, 11_051) > 0.5);
+
+    let mut reader = looped_reader();
+    reader.set_loop_crossfade(HOST_BUFFER_SIZE);
+    let loop_len = 11_051 - HOST_BUFFER_SIZE;
+    assert_eq!(reader.buffer.channel_capacity() % HOST_BUFFER_SIZE, 0);
+    assert!(seam_delta(&reader, loop_len) < sine_delta * 1.5);
+
+    // Slices stay host buffer long and carry on from the start of the loop across its end
+    let looped = reader.buffer.left[..loop_len].to_vec();
+    let slices = 3 * loop_len / HOST_BUFFER_SIZE;
+    for slice in 0..slices {
+        let (left, right) = reader.next_slice().unwrap();
+        assert_eq!(left.len(), HOST_BUFFER_SIZE);
+        assert_eq!(right.len(), HOST_BUFFER_SIZE);
+        for (frame, sample) in left.iter().enumerate() {
+            assert_eq!(
+                *sample,
+                looped[(slice * HOST_BUFFER_SIZE + frame) % loop_len]
+            );
+        }
+    }
+}
+
+#[test]
+fn fail_to_slice_past_the_buffer() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+    reader.next_slice().unwrap();
+
+    // The buffer is public, it may shrink behind the reading cursor
+    reader.buffer.resize(HOST_BUFFER_SIZE / 2);
+    reader.buffer.trim();
+    assert!(matches!(
+        reader.next_slice(),
+        Err(SampleLoadError::BufferError(_))
+    ));
+}
+
+#[test]
+fn iterate_over_slices_once() {
+    stereo_float_sine();
+    let pregen_sine = sine_float_samples();
+    let full_slices = SAMPLE_RATE as usize / HOST_BUFFER_SIZE;
+
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+    assert_eq!(reader.slices().count(), full_slices + 1);
+    assert!(reader
+        .slices()
+        .all(|(left, right)| left.len() == HOST_BUFFER_SIZE && right.len() == HOST_BUFFER_SIZE));
+
+    let (left, right): (Vec<f32>, Vec<f32>) = reader
+        .slices()
+        .flat_map(|(left, right)| left.iter().copied().zip(right.iter().copied()))
+        .unzip();
+    assert_eq!(&left[..pregen_sine.len()], pregen_sine.as_slice());
+    assert_eq!(&right[..pregen_sine.len()], pregen_sine.as_slice());
+    assert_silence(&left[pregen_sine.len()..]);
+
+    // The shorter final slice follows the partial slice policy
+    let reader = |policy| {
+        let mut reader =
+            SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+                .align(false)
+                .partial_slice_policy(policy)
+                .build()
+                .unwrap();
+        reader.read_sync().unwrap();
+        reader
+    };
+    let truncating = reader(PartialSlicePolicy::Truncate);
+    assert_eq!(truncating.slices().count(), full_slices + 1);
+    let (last, _) = truncating.slices().last().unwrap();
+    assert_eq!(last.len(), SAMPLE_RATE as usize % HOST_BUFFER_SIZE);
+    assert_eq!(
+        reader(PartialSlicePolicy::Skip).slices().count(),
+        full_slices
+    );
+}
+
+#[test]
+fn collect_packet_frame_stats() {
+    stereo_float_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .collect_packet_stats(true)
+        .build()
+        .unwrap();
+
+    reader.read_sync().unwrap();
+    let stats = reader.packet_frame_stats();
+
+    assert!(stats.count > 0);
+    assert!(stats.min <= stats.max);
+    assert_eq!(
+        (stats.mean * stats.count as f32).round() as usize,
+        SAMPLE_RATE as usize
+    );
+}
+
+#[test]
+fn accumulate_overall_rms() {
+    stereo_float_sine();
+
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+
+    let rms_error = f64::abs(reader.overall_rms() as f64 - std::f64::consts::FRAC_1_SQRT_2);
+    assert!(
+        rms_error < ACCEPTABLE_FLOAT_ERROR,
+        "RMS error {}",
+        rms_error
+    );
+}
+
+#[test]
+fn normalize_loaded_sine() {
+    stereo_float_sine();
+
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+
+    reader.buffer.normalize_peak(0.5);
+    let peak_error = f64::abs(reader.buffer.stats().peak as f64 - 0.5);
+    assert!(
+        peak_error < ACCEPTABLE_FLOAT_ERROR,
+        "Peak error {}",
+        peak_error
+    );
+
+    // Silence is left untouched
+    let mut silence = StereoBuffer::new(4);
+    silence.append_slices(&[0.0; 4], &[0.0; 4]);
+    silence.normalize_peak(0.5);
+    assert_eq!(silence.stats().peak, 0.0);
+}
+
+#[test]
+fn resample_to_target_rate() {
+    stereo_float_sine();
+    let resampled_reader = |target_rate: u32| {
+        let mut reader = SyncFullReader::new_resampled(
+            PathBuf::from(FLOAT_STEREO_SINE),
+            HOST_BUFFER_SIZE,
+            UpmixPolicy::DuplicateMono,
+            target_rate,
+            ResampleQuality::Linear,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
+        .unwrap();
+        reader.read_sync().unwrap();
+        reader
+    };
+
+    // Same rate is an identity
+    let reader = resampled_reader(SAMPLE_RATE);
+    let pregen_sine = sine_float_samples();
+    assert_eq!(reader.sample_rate(), SAMPLE_RATE);
+    assert_integrity(&pregen_sine, &pregen_sine, &reader.buffer, ACCEPTABLE_ERROR);
+
+    // A second of 440Hz keeps its 440 periods
+    let reader = resampled_reader(48_000);
+    assert_eq!(reader.sample_rate(), 48_000);
+    assert_eq!(reader.buffer.capacity() % HOST_BUFFER_SIZE, 0);
+    let (left, _) = reader.buffer.slice(0, 48_000);
+    let periods = left
+        .windows(2)
+        .filter(|pair| pair[0] < 0.0 && pair[1] >= 0.0)
+        .count();
+    assert!((439..=441).contains(&periods), "{} periods", periods);
+}
+
+#[test]
+fn read_behind_large_id3v2_tag() {
+    mono_int_sine();
+    // A picture larger than the probe search limit
+    prepend_id3v2_tag(INT_MONO_SINE, ID3_MONO_SINE, 2 * 1024 * 1024);
+
+    let mut reader = default_reader(PathBuf::from(ID3_MONO_SINE));
+
+    reader.read_sync().unwrap();
+    assert_eq!(reader.buffer.capacity() % HOST_BUFFER_SIZE, 0);
+
+    let pregen_sine = sine_float_samples();
+    assert_integrity(
+        &pregen_sine,
+        &pregen_sine,
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+}
+
+#[test]
+fn index_packet_timestamps() {
+    stereo_float_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .index_timestamps(true)
+        .build()
+        .unwrap();
+
+    reader.read_sync().unwrap();
+    let index = reader.timestamp_index();
+
+    assert!(!index.is_empty());
+    assert_eq!(index[0], (0, 0));
+    // Wav timestamps are expressed in frames
+    assert!(index.iter().all(|(frame, ts)| *frame as u64 == *ts));
+}
+
+#[test]
+fn seek_through_built_index() {
+    stereo_float_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .build_seek_index(true)
+        .build()
+        .unwrap();
+
+    reader.read_sync().unwrap();
+    let points = reader.seek_index().unwrap().points();
+
+    assert_eq!(points[0], (0, 0));
+    // Sparse, unlike the per packet timestamp index
+    let interval = SAMPLE_RATE as usize / 10;
+    assert!(points
+        .windows(2)
+        .all(|pair| pair[1].1 - pair[0].1 >= interval));
+
+    let half = SAMPLE_RATE as usize / 2;
+    assert_eq!(reader.seek(Time::from(0.5)), half);
+
+    let pregen_sine = sine_float_samples();
+    let target = &pregen_sine[half..half + HOST_BUFFER_SIZE];
+    let (left, right) = reader.next_slice().unwrap();
+    assert_eq!(left, target);
+    assert_eq!(right, target);
+
+    // Seeking past the end lands at the end of the content
+    assert_eq!(reader.seek(Time::from(10.0)), SAMPLE_RATE as usize);
+    assert!(default_reader(PathBuf::from(FLOAT_STEREO_SINE))
+        .seek_index()
+        .is_none());
+}
+
+#[test]
+fn copy_into_fixed_slices() {
+    mono_int_sine();
+
+    let mut reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    let mut left = [1f32; HOST_BUFFER_SIZE];
+    let mut right = [1f32; HOST_BUFFER_SIZE];
+    let full_slices = SAMPLE_RATE as usize / HOST_BUFFER_SIZE;
+    let tail = SAMPLE_RATE as usize % HOST_BUFFER_SIZE;
+
+    for i in 0..full_slices {
+        assert_eq!(reader.next_into(&mut left, &mut right), SliceState::Full);
+        let target = &pregen_sine[i * HOST_BUFFER_SIZE..(i + 1) * HOST_BUFFER_SIZE];
+        assert_eq!(
+            error_smaller_than(target, &left, ACCEPTABLE_FLOAT_ERROR),
+            None
+        );
+        assert_eq!(
+            error_smaller_than(target, &right, ACCEPTABLE_FLOAT_ERROR),
+            None
+        );
+    }
+
+    assert_eq!(
+        reader.next_into(&mut left, &mut right),
+        SliceState::PartiallyPadded(tail)
+    );
+    let target = &pregen_sine[full_slices * HOST_BUFFER_SIZE..];
+    assert_eq!(
+        error_smaller_than(target, &left[..tail], ACCEPTABLE_FLOAT_ERROR),
+        None
+    );
+    assert_silence(&left[tail..]);
+    assert_silence(&right[tail..]);
+
+    assert_eq!(reader.next_into(&mut left, &mut right), SliceState::End);
+    assert_silence(&left);
+    assert_silence(&right);
+}
+
+#[test]
+fn read_past_understated_frame_count() {
+    flac_mono_sine_stating(
+        MISCOUNTED_FLAC_MONO_SINE,
+        false,
+        Some(SAMPLE_RATE as usize / 2),
+    );
+
+    let mut reader = default_reader(PathBuf::from(MISCOUNTED_FLAC_MONO_SINE));
+    assert_eq!(reader.buffer.channel_capacity(), SAMPLE_RATE as usize / 2);
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    assert_integrity(
+        &pregen_sine,
+        &pregen_sine,
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+}
+
+#[test]
+fn keep_overflow_of_final_packet() {
+    // The last packet overflows the stated count, and does not span a whole block
+    let frames = SAMPLE_RATE as usize;
+    assert_ne!(frames % FLAC_BLOCK_SIZE, 0);
+    flac_mono_sine_stating(TAIL_MISCOUNTED_FLAC_MONO_SINE, false, Some(frames - 100));
+
+    let mut reader = SyncFullReader::builder(
+        PathBuf::from(TAIL_MISCOUNTED_FLAC_MONO_SINE),
+        HOST_BUFFER_SIZE,
+    )
+    .align(false)
+    .build()
+    .unwrap();
+    reader.read_sync().unwrap();
+    assert_eq!(reader.buffer.cursor(), frames);
+    assert_eq!(reader.buffer.channel_capacity(), frames);
+
+    let pregen_sine = sine_float_samples();
+    assert_integrity(
+        &pregen_sine,
+        &pregen_sine,
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+}
+
+#[test]
+fn verify_flac_checksum() {
+    flac_mono_sine(FLAC_MONO_SINE, false);
+    flac_mono_sine(CORRUPTED_FLAC_MONO_SINE, true);
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLAC_MONO_SINE), HOST_BUFFER_SIZE)
+        .with_verify(true)
+        .build()
+        .unwrap();
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    assert_integrity(
+        &pregen_sine,
+        &pregen_sine,
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+
+    let mut reader =
+        SyncFullReader::builder(PathBuf::from(CORRUPTED_FLAC_MONO_SINE), HOST_BUFFER_SIZE)
+            .with_verify(true)
+            .build()
+            .unwrap();
+    assert!(matches!(
+        reader.read_sync(),
+        Err(SampleLoadError::VerificationFailed)
+    ));
+
+    // Without verification the corruption goes unnoticed
+    let mut reader = default_reader(PathBuf::from(CORRUPTED_FLAC_MONO_SINE));
+    reader.read_sync().unwrap();
+}
+
+#[test]
+fn swap_channels_while_decoding() {
+    left_only_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(LEFT_ONLY_SINE), HOST_BUFFER_SIZE)
+        .channel_map(ChannelMap::SwapLR)
+        .build()
+        .unwrap();
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    let silence = vec![0f32; pregen_sine.len()];
+    assert_integrity(&silence, &pregen_sine, &reader.buffer, ACCEPTABLE_ERROR);
+}
+
+#[test]
+fn report_sample_conversion() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+    assert!(!reader.used_conversion());
+
+    mono_int_sine();
+    let mut reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    reader.read_sync().unwrap();
+    assert!(reader.used_conversion());
+}
+
+#[test]
+fn issue_blocks_of_slices() {
+    stereo_float_sine();
+
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    let block_len = 5 * HOST_BUFFER_SIZE;
+    let blocks = reader.buffer.channel_capacity() / block_len;
+
+    for i in 0..blocks {
+        let (left, right) = reader.next_block(5).unwrap();
+        assert_eq!(left.len(), block_len);
+        let start = i * block_len;
+        let end = std::cmp::min(start + block_len, pregen_sine.len());
+        let target = &pregen_sine[start..end];
+        assert_eq!(error_smaller_than(target, left, ACCEPTABLE_ERROR), None);
+        assert_eq!(error_smaller_than(target, right, ACCEPTABLE_ERROR), None);
+    }
+
+    // 44 host buffers hold the sample, the last 4 do not fill a block of 5
+    assert!(reader.next_block(5).is_none());
+    let (left, _) = reader.next_slice().unwrap();
+    assert_eq!(left.len(), HOST_BUFFER_SIZE);
+}
+
+#[test]
+fn read_sample_by_sample() {
+    stereo_float_sine();
+
+    let mut reader = SyncFullReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .align(false)
+        .build()
+        .unwrap();
+    assert!(reader.next_sample(Channel::Left).is_err());
+    reader.read_sync().unwrap();
+
+    let pregen_sine = sine_float_samples();
+    for expected in pregen_sine.iter() {
+        assert_eq!(reader.next_sample(Channel::Left).unwrap(), *expected);
+    }
+    // Channels advance independently, and round robin
+    assert_eq!(reader.next_sample(Channel::Right).unwrap(), pregen_sine[0]);
+    assert_eq!(reader.next_sample(Channel::Left).unwrap(), pregen_sine[0]);
+    assert_eq!(reader.next_sample(Channel::Left).unwrap(), pregen_sine[1]);
+
+    // Slices are not affected
+    assert_eq!(
+        reader.next_slice().unwrap().0,
+        &pregen_sine[..HOST_BUFFER_SIZE]
+    );
+}
+
+#[test]
+fn reach_refill_threshold() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+    let slices = reader.buffer.channel_capacity() / HOST_BUFFER_SIZE;
+
+    // Consumption is measured per channel, so high thresholds are reached before wrapping around
+    let mut issued = 0;
+    while !reader.needs_refill(0.9) {
+        reader.next_slice().unwrap();
+        issued += 1;
+        assert!(issued < slices);
+    }
+    assert_eq!(issued, (0.9 * slices as f32).ceil() as usize);
+}
+
+#[test]
+fn rewind_and_seek_samples() {
+    stereo_float_sine();
+
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    reader.read_sync().unwrap();
+    let pregen_sine = sine_float_samples();
+
+    reader.next_slice().unwrap();
+    reader.next_slice().unwrap();
+    reader.next_sample(Channel::Left).unwrap();
+    reader.rewind();
+    assert_eq!(reader.percentage_consumed(), 0.0);
+    assert_eq!(
+        reader.next_slice().unwrap().0,
+        &pregen_sine[..HOST_BUFFER_SIZE]
+    );
+    assert_eq!(reader.next_sample(Channel::Left).unwrap(), pregen_sine[0]);
+
+    reader.seek_samples(100);
+    assert_eq!(
+        reader.next_slice().unwrap().0,
+        &pregen_sine[100..100 + HOST_BUFFER_SIZE]
+    );
+
+    // Out of range indices land on the last frame
+    reader.seek_samples(usize::MAX);
+    let (left, _) = reader.next_slice().unwrap();
+    assert_eq!(left.len(), 1);
+}
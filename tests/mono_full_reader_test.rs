@@ -0,0 +1,70 @@
+mod common;
+
+use audio_reader::prelude::*;
+use common::*;
+
+use std::path::PathBuf;
+
+/// Get a SyncMonoFullReader for a given file
+fn default_reader(path: PathBuf) -> SyncMonoFullReader {
+    SyncMonoFullReader::new(
+        path,
+        HOST_BUFFER_SIZE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap()
+}
+
+fn assert_mono_integrity(target: &[f32], buffer: &MonoBuffer, error_threshold: f64) {
+    for (sample, expected) in buffer.slice(0, target.len()).iter().zip(target) {
+        assert!(((sample - expected).abs() as f64) < error_threshold);
+    }
+    assert!(buffer.as_slice()[target.len()..]
+        .iter()
+        .all(|sample| *sample == 0.0));
+}
+
+#[test]
+fn read_mono_int_wav() {
+    mono_int_sine();
+    let mut reader = default_reader(PathBuf::from(INT_MONO_SINE));
+
+    reader.read_sync().unwrap();
+    assert_eq!(reader.buffer.capacity() % HOST_BUFFER_SIZE, 0);
+    assert_mono_integrity(
+        &sine_float_samples(),
+        &reader.buffer,
+        ACCEPTABLE_FLOAT_ERROR,
+    );
+}
+
+#[test]
+fn mix_down_stereo_float_wav() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+
+    reader.read_sync().unwrap();
+    assert_mono_integrity(&sine_float_samples(), &reader.buffer, ACCEPTABLE_ERROR);
+}
+
+#[test]
+fn issue_mono_slices() {
+    mono_int_sine();
+    let mut reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    reader.read_sync().unwrap();
+
+    let capacity = reader.buffer.channel_capacity();
+    let pregen_sine = sine_float_samples();
+    let first = reader.next_slice().unwrap().to_vec();
+    assert_eq!(first.len(), HOST_BUFFER_SIZE);
+    assert!((first[10] - pregen_sine[10]).abs() < ACCEPTABLE_FLOAT_ERROR as f32);
+
+    for _ in 1..capacity / HOST_BUFFER_SIZE {
+        reader.next_slice().unwrap();
+    }
+    // Round robin to the start
+    assert_eq!(reader.percentage_consumed(), 0.0);
+    assert_eq!(reader.next_slice().unwrap(), first.as_slice());
+}
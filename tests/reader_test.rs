@@ -0,0 +1,416 @@
+mod common;
+
+use audio_reader::prelude::*;
+use common::*;
+
+use std::{error::Error, io, path::PathBuf, time::Duration};
+use symphonia::core::{audio::Layout, io::ReadOnlySource, meta::StandardTagKey, units::TimeBase};
+
+/// Get a Reader for a given file
+fn default_reader(path: PathBuf) -> Reader {
+    Reader::new(
+        path,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn report_layout_name() {
+    mono_int_sine();
+    let reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    assert_eq!(reader.meta.layout_name(), "Mono");
+
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.meta.layout_name(), "Stereo");
+}
+
+#[test]
+fn report_container_name() {
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.meta.container_name(), "WAV");
+
+    tagged_stereo_sine();
+    let reader = default_reader(PathBuf::from(TAGGED_STEREO_SINE));
+    assert_eq!(reader.meta.container_name(), "WAV");
+}
+
+#[test]
+fn report_codec_name() {
+    mono_int_sine();
+    let reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    assert_eq!(reader.meta.codec_name(), "pcm_s16le");
+
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.meta.codec_name(), "pcm_f32le");
+
+    flac_mono_sine(FLAC_MONO_SINE, false);
+    let reader = default_reader(PathBuf::from(FLAC_MONO_SINE));
+    assert_eq!(reader.meta.codec_name(), "flac");
+    assert_eq!(reader.meta.container_name(), "FLAC");
+}
+
+#[test]
+fn report_channel_count() {
+    mono_int_sine();
+    let reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    assert_eq!(reader.channel_count(), 1);
+    assert!(matches!(reader.layout(), Layout::Mono));
+
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.channel_count(), 2);
+    assert!(matches!(reader.layout(), Layout::Stereo));
+}
+
+#[test]
+fn compute_peaks_per_bucket() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let pregen_sine = sine_float_samples();
+
+    let peaks = reader.compute_peaks(10).unwrap();
+    assert_eq!(peaks.len(), 10);
+    for (peak, chunk) in peaks
+        .iter()
+        .zip(pregen_sine.chunks(SAMPLE_RATE as usize / 10))
+    {
+        let min = chunk.iter().copied().fold(f32::INFINITY, f32::min);
+        let max = chunk.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+        assert_eq!(*peak, (min, max));
+    }
+
+    // More buckets than frames leaves some of them empty
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let peaks = reader.compute_peaks(SAMPLE_RATE as usize * 2).unwrap();
+    assert_eq!(peaks[1], (0.0, 0.0));
+    assert_eq!(peaks[2], (pregen_sine[1], pregen_sine[1]));
+}
+
+#[test]
+fn report_seekability_and_known_duration() {
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert!(reader.is_seekable());
+    assert!(reader.duration_is_known());
+
+    // Piped sources cannot seek
+    let file = std::fs::File::open(FLOAT_STEREO_SINE).unwrap();
+    let reader = Reader::from_media_source(
+        Box::new(ReadOnlySource::new(file)),
+        Some("wav"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    assert!(!reader.is_seekable());
+
+    // A frame count of 0 states that it is unknown
+    flac_mono_sine_stating(UNCOUNTED_FLAC_MONO_SINE, false, Some(0));
+    let reader = default_reader(PathBuf::from(UNCOUNTED_FLAC_MONO_SINE));
+    assert!(!reader.duration_is_known());
+}
+
+#[test]
+fn estimate_capacity() {
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.meta.estimated_capacity(), SAMPLE_RATE as usize);
+
+    let mut meta = reader.meta.clone();
+    meta.n_samples = None;
+    assert_eq!(meta.estimated_capacity(), 1024 * 1024 / 8);
+}
+
+#[test]
+fn report_sample_count() {
+    mono_int_sine();
+    let reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    assert_eq!(reader.meta.n_samples, Some(SAMPLE_RATE as u64));
+    assert!(reader.meta.max_samples_per_packet.is_some());
+
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.meta.n_samples, Some(SAMPLE_RATE as u64));
+    assert!(reader.meta.max_samples_per_packet.is_some());
+}
+
+#[test]
+fn lookup_standard_tags() {
+    tagged_stereo_sine();
+    let reader = default_reader(PathBuf::from(TAGGED_STEREO_SINE));
+
+    assert_eq!(
+        reader.meta.tag(StandardTagKey::TrackTitle),
+        Some(SINE_TITLE)
+    );
+    assert_eq!(reader.meta.tag(StandardTagKey::Artist), Some(SINE_ARTIST));
+    assert_eq!(reader.meta.tag(StandardTagKey::Album), None);
+    assert!(reader.meta.tags.iter().any(|tag| tag.key == "INAM"));
+}
+
+#[test]
+fn expose_track_info_tags() {
+    tagged_stereo_sine();
+    let reader = default_reader(PathBuf::from(TAGGED_STEREO_SINE));
+    assert_eq!(
+        reader.tags(),
+        vec![
+            ("title".to_owned(), SINE_TITLE.to_owned()),
+            ("artist".to_owned(), SINE_ARTIST.to_owned()),
+        ]
+    );
+
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert!(reader.tags().is_empty());
+}
+
+#[test]
+fn convert_frames_and_timestamps() {
+    stereo_float_sine();
+    let reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+
+    // Wav timestamps are expressed in frames
+    assert_eq!(reader.meta.frames_to_ts(12345), 12345);
+    assert_eq!(reader.meta.ts_to_frames(12345), 12345);
+
+    // Ten hours at millisecond resolution, starting late
+    let mut meta = reader.meta.clone();
+    meta.time_base = TimeBase::new(1, 1000);
+    meta.start_ts = 500;
+    let ten_hours = 10 * 3600 * SAMPLE_RATE as u64;
+    assert_eq!(meta.frames_to_ts(ten_hours), 10 * 3600 * 1000 + 500);
+    assert_eq!(meta.ts_to_frames(10 * 3600 * 1000 + 500), ten_hours);
+    assert_eq!(meta.ts_to_frames(0), 0);
+
+    // Two days at a high rate with a very fine time base would overflow 64 bit intermediates
+    let mut meta = reader.meta.clone();
+    meta.sample_rate = 192000;
+    meta.time_base = TimeBase::new(1, 705_600_000);
+    let two_days = 48 * 3600 * 192000u64;
+    let two_days_ts = 48 * 3600 * 705_600_000u64;
+    assert_eq!(meta.frames_to_ts(two_days), two_days_ts);
+    assert_eq!(meta.ts_to_frames(two_days_ts), two_days);
+}
+
+#[test]
+fn seek_with_preroll() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let pregen_sine = sine_float_samples();
+
+    // The preroll of targets near the start reaches before it
+    for start in [0, 100, 22050] {
+        let position = Duration::from_secs_f64(start as f64 / SAMPLE_RATE as f64);
+        let ts = reader.seek_accurate(position, 4).unwrap();
+        assert_eq!(ts, start as u64);
+
+        // The prerolled frames are discarded, the window only holds content past the target
+        let mut window = StereoBuffer::new(HOST_BUFFER_SIZE);
+        let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+        while window.capacity_left() > 0 {
+            reader.next_packet(&mut window, &mut remainder).unwrap();
+            remainder.clear_cursor();
+        }
+
+        let target = &pregen_sine[start..start + HOST_BUFFER_SIZE];
+        assert_integrity(target, target, &window, ACCEPTABLE_ERROR);
+    }
+}
+
+#[test]
+fn seek_in_millisecond_time_base() {
+    mkv_flac_mono_sine(MKV_FLAC_MONO_SINE);
+    let mut reader = default_reader(PathBuf::from(MKV_FLAC_MONO_SINE));
+    let time_base = reader.meta.time_base;
+    assert_eq!(time_base.denom / time_base.numer, 1000);
+    let pregen_sine = sine_float_samples();
+
+    // The seek lands on the preceding block, whose frames up to the target are discarded
+    assert_eq!(reader.seek(Duration::from_millis(250)).unwrap(), 11025);
+
+    let mut window = StereoBuffer::new(HOST_BUFFER_SIZE);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+    while window.capacity_left() > 0 {
+        reader.next_packet(&mut window, &mut remainder).unwrap();
+        remainder.clear_cursor();
+    }
+    let target = &pregen_sine[11025..11025 + HOST_BUFFER_SIZE];
+    assert_integrity(target, target, &window, ACCEPTABLE_FLOAT_ERROR);
+    // The window spans three 10ms blocks, the last one starting at 270ms
+    assert_eq!(reader.decoded_position(), 270);
+}
+
+#[test]
+fn preroll_discards_packets() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let pregen_sine = sine_float_samples();
+
+    let discarded = reader.preroll(2).unwrap();
+    assert!(discarded > 0);
+
+    let mut window = StereoBuffer::new(HOST_BUFFER_SIZE);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+    reader.next_packet(&mut window, &mut remainder).unwrap();
+
+    let target = &pregen_sine[discarded..discarded + HOST_BUFFER_SIZE];
+    assert_integrity(target, target, &window, ACCEPTABLE_ERROR);
+
+    // Prerolling past the end only discards what is left
+    let left = SAMPLE_RATE as usize - discarded - window.cursor() - remainder.cursor();
+    assert_eq!(reader.preroll(usize::MAX).unwrap(), left);
+}
+
+#[test]
+fn track_decoded_position() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    assert_eq!(reader.decoded_position(), 0);
+
+    let mut window = StereoBuffer::new(SAMPLE_RATE as usize);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+    reader.next_packet(&mut window, &mut remainder).unwrap();
+    assert_eq!(reader.decoded_position(), 0);
+
+    let first_packet = window.cursor() as u64;
+    reader.next_packet(&mut window, &mut remainder).unwrap();
+    assert_eq!(reader.decoded_position(), first_packet);
+}
+
+#[test]
+fn report_open_warnings() {
+    mono_int_sine();
+    let reader = default_reader(PathBuf::from(INT_MONO_SINE));
+    let warnings = reader.meta.open_warnings();
+
+    // WAV does not carry gapless information
+    assert!(warnings.iter().any(|warning| warning.contains("delay")));
+    assert!(warnings.iter().any(|warning| warning.contains("padding")));
+    assert!(!warnings
+        .iter()
+        .any(|warning| warning.contains("frame count")));
+}
+
+#[test]
+fn infer_missing_channel_layout() {
+    // Plain PCM WAVs only state their channel count
+    for (generate, path, layout) in [
+        (mono_int_sine as fn(), INT_MONO_SINE, "Mono"),
+        (stereo_float_sine, FLOAT_STEREO_SINE, "Stereo"),
+    ] {
+        generate();
+        let reader = default_reader(PathBuf::from(path));
+        assert_eq!(reader.meta.layout_name(), layout);
+        assert!(reader
+            .meta
+            .open_warnings()
+            .iter()
+            .any(|warning| warning.contains("channel layout")));
+    }
+}
+
+#[test]
+fn chain_error_sources() {
+    let error = Reader::new(
+        PathBuf::from("assets/missing.wav"),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .err()
+    .unwrap();
+    let source = error.source().unwrap().downcast_ref::<io::Error>();
+    assert_eq!(source.unwrap().kind(), io::ErrorKind::NotFound);
+
+    let error = SampleDecodeError::LoadError(error);
+    let root = error.source().and_then(Error::source).unwrap();
+    assert!(root.is::<io::Error>());
+
+    // Each layer only describes itself, the message of its source is not repeated
+    let mut current: Option<&dyn Error> = Some(&error);
+    while let Some(layer) = current {
+        if let Some(source) = layer.source() {
+            assert!(!layer.to_string().contains(&source.to_string()));
+        }
+        current = layer.source();
+    }
+
+    assert!(SampleLoadError::VerificationFailed.source().is_none());
+}
+
+#[test]
+fn convert_into_load_errors() {
+    let error = SampleLoadError::from(io::Error::from(io::ErrorKind::NotFound));
+    assert!(matches!(error, SampleLoadError::IoError(_)));
+
+    let error: SampleDecodeError = error.into();
+    assert!(matches!(
+        error,
+        SampleDecodeError::LoadError(SampleLoadError::IoError(_))
+    ));
+}
+
+#[test]
+fn seek_to_duration() {
+    stereo_float_sine();
+    let mut reader = default_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let pregen_sine = sine_float_samples();
+
+    let mut window = StereoBuffer::new(HOST_BUFFER_SIZE);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+
+    // Past the end, the reader is clamped to the end
+    assert_eq!(
+        reader.seek(Duration::from_secs(5)).unwrap(),
+        SAMPLE_RATE as u64
+    );
+    assert!(matches!(
+        reader.next_packet(&mut window, &mut remainder).unwrap(),
+        ReadingProjection::EndReached
+    ));
+
+    // Landing in the middle of a packet is sample accurate
+    let start = 22_050 + 7;
+    let ts = reader
+        .seek(Duration::from_secs_f64(start as f64 / SAMPLE_RATE as f64))
+        .unwrap();
+    assert_eq!(ts, start as u64);
+    while window.capacity_left() > 0 {
+        remainder.clear_cursor();
+        reader.next_packet(&mut window, &mut remainder).unwrap();
+    }
+
+    let target = &pregen_sine[start..start + HOST_BUFFER_SIZE];
+    assert_integrity(target, target, &window, ACCEPTABLE_ERROR);
+}
+
+#[test]
+fn read_from_bytes() {
+    stereo_float_sine();
+    let data = std::fs::read(FLOAT_STEREO_SINE).unwrap();
+    let mut reader = Reader::from_bytes(data, Some("wav")).unwrap();
+    let pregen_sine = sine_float_samples();
+
+    assert!(matches!(
+        reader.meta.source,
+        SourceDescriptor::Memory { extension: Some(ref ext), .. } if ext == "wav"
+    ));
+    assert_eq!(reader.meta.container_name(), "WAV");
+
+    let mut window = StereoBuffer::new(SAMPLE_RATE as usize);
+    let mut remainder = StereoBuffer::new(SAMPLE_RATE as usize);
+    while let ReadingProjection::SamplesRead(_) =
+        reader.next_packet(&mut window, &mut remainder).unwrap()
+    {}
+
+    assert_eq!(window.cursor(), SAMPLE_RATE as usize);
+    assert_integrity(&pregen_sine, &pregen_sine, &window, ACCEPTABLE_ERROR);
+}
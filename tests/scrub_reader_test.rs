@@ -0,0 +1,35 @@
+mod common;
+
+use audio_reader::prelude::*;
+use common::*;
+
+use std::path::PathBuf;
+use symphonia::core::units::Time;
+
+/// Get a ScrubReader for a given file
+fn default_scrub_reader(path: PathBuf) -> ScrubReader {
+    ScrubReader::new(
+        path,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn scrub_back_and_forth() {
+    stereo_float_sine();
+
+    let mut reader = default_scrub_reader(PathBuf::from(FLOAT_STEREO_SINE));
+    let pregen_sine = sine_float_samples();
+
+    for start in [22050usize, 1000, 30000, 0] {
+        let time = Time::from(start as f64 / SAMPLE_RATE as f64);
+        let window = reader.window_at(time, HOST_BUFFER_SIZE).unwrap();
+        let target = &pregen_sine[start..start + HOST_BUFFER_SIZE];
+
+        assert_eq!(window.channel_capacity(), HOST_BUFFER_SIZE);
+        assert_integrity(target, target, &window, ACCEPTABLE_ERROR);
+    }
+}
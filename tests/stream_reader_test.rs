@@ -0,0 +1,212 @@
+mod common;
+
+use audio_reader::prelude::*;
+use common::*;
+
+use std::path::PathBuf;
+
+const TARGET_RATE: u32 = 48000;
+/// Upper bound of the linear interpolation error of a 440Hz sine sampled at 44100Hz
+const ACCEPTABLE_RESAMPLE_ERROR: f64 = 0.001;
+
+#[test]
+fn read_pages_without_resampling() {
+    stereo_float_sine();
+
+    let mut reader = SyncStreamReader::new(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        HOST_BUFFER_SIZE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reader.sample_rate(), SAMPLE_RATE);
+
+    let pregen_sine = sine_float_samples();
+    let mut read = 0;
+    while let ReadingProjection::SamplesRead(frames) = reader.read_next_page().unwrap() {
+        let target = &pregen_sine[read..read + frames];
+        let (left, right) = reader.page.slice(0, frames);
+        assert_eq!(error_smaller_than(target, left, ACCEPTABLE_ERROR), None);
+        assert_eq!(error_smaller_than(target, right, ACCEPTABLE_ERROR), None);
+        assert_silence(&reader.page.left.as_slice()[frames..]);
+        read += frames;
+    }
+
+    assert_eq!(read, SAMPLE_RATE as usize);
+
+    // The padded silence of the last page is not accounted for
+    let stats = reader.stats();
+    assert_eq!(stats.samples, 2 * SAMPLE_RATE as usize);
+    assert!((stats.rms - std::f32::consts::FRAC_1_SQRT_2).abs() < 0.001);
+    assert!(stats.peak <= 1.0 && stats.peak > 0.999);
+}
+
+#[test]
+fn resample_pages_continuously() {
+    stereo_float_sine();
+
+    let mut reader = SyncStreamReader::new_resampled(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        HOST_BUFFER_SIZE,
+        TARGET_RATE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    assert_eq!(reader.sample_rate(), TARGET_RATE);
+
+    let mut left = Vec::new();
+    let mut right = Vec::new();
+    while let ReadingProjection::SamplesRead(frames) = reader.read_next_page().unwrap() {
+        let (page_left, page_right) = reader.page.slice(0, frames);
+        left.extend_from_slice(page_left);
+        right.extend_from_slice(page_right);
+    }
+
+    // The frames held back by the resampler are issued too, just like a full read
+    let mut full_reader = SyncFullReader::new_resampled(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        HOST_BUFFER_SIZE,
+        UpmixPolicy::DuplicateMono,
+        TARGET_RATE,
+        ResampleQuality::Linear,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    full_reader.read_sync().unwrap();
+    let mut full_len = 0;
+    let (mut slice_left, mut slice_right) =
+        (vec![0f32; HOST_BUFFER_SIZE], vec![0f32; HOST_BUFFER_SIZE]);
+    loop {
+        match full_reader.next_into(&mut slice_left, &mut slice_right) {
+            SliceState::Full => full_len += HOST_BUFFER_SIZE,
+            SliceState::PartiallyPadded(frames) => full_len += frames,
+            SliceState::End => break,
+        }
+    }
+    assert_eq!(left.len(), full_len);
+
+    // Page boundaries fall every HOST_BUFFER_SIZE frames, any discontinuity would exceed the error.
+    // Frames past the last source frame, flushed at the end, hold it.
+    let last = (SAMPLE_RATE - 1) as f32 / SAMPLE_RATE as f32;
+    let target: Vec<f32> = (0..left.len())
+        .map(|x| sine_sample(f32::min(x as f32 / TARGET_RATE as f32, last)))
+        .collect();
+    assert_eq!(
+        error_smaller_than(&target, &left, ACCEPTABLE_RESAMPLE_ERROR),
+        None
+    );
+    assert_eq!(
+        error_smaller_than(&target, &right, ACCEPTABLE_RESAMPLE_ERROR),
+        None
+    );
+}
+
+#[test]
+fn peek_ahead_of_the_cursor() {
+    stereo_float_sine();
+
+    let stream = SyncStreamReader::new(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        HOST_BUFFER_SIZE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    let lookahead = 3 * HOST_BUFFER_SIZE;
+    let mut reader = LookaheadReader::new(stream, lookahead).unwrap();
+
+    let pregen_sine = sine_float_samples();
+    let mut read = 0;
+    while !reader.is_finished() {
+        let expected = std::cmp::min(lookahead, pregen_sine.len() - read);
+        assert!(reader.available() >= expected);
+
+        // Peeking does not advance
+        let (left, right) = reader.peek(lookahead);
+        assert_eq!(left.len(), expected);
+        assert_eq!(left, &pregen_sine[read..read + expected]);
+        assert_eq!(right, &pregen_sine[read..read + expected]);
+        assert_eq!(reader.peek(lookahead).0.len(), expected);
+
+        let frames = std::cmp::min(HOST_BUFFER_SIZE / 2, expected);
+        reader.advance(frames).unwrap();
+        read += frames;
+    }
+
+    assert_eq!(read, SAMPLE_RATE as usize);
+}
+
+#[test]
+fn refill_through_sample_reader() {
+    stereo_float_sine();
+
+    let mut reader = SyncStreamReader::new(
+        PathBuf::from(FLOAT_STEREO_SINE),
+        HOST_BUFFER_SIZE,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+    .unwrap();
+    reader.read_sync().unwrap();
+    assert!(!reader.needs_refill(1.0));
+
+    let pregen_sine = sine_float_samples();
+    for chunk in pregen_sine.chunks(HOST_BUFFER_SIZE) {
+        if reader.needs_refill(1.0) {
+            reader.refill().unwrap();
+        }
+        let (left, right) = reader.next_slice().unwrap();
+        assert_eq!(&left[..chunk.len()], chunk);
+        assert_eq!(&right[..chunk.len()], chunk);
+        assert!(reader.needs_refill(1.0));
+    }
+
+    // Past the end, only silence is issued
+    reader.refill().unwrap();
+    let (left, _) = reader.next_slice().unwrap();
+    assert_silence(left);
+}
+
+#[test]
+fn issue_slices_from_a_ring() {
+    stereo_float_sine();
+
+    let ring_slices = 4;
+    let mut reader = SyncStreamReader::builder(PathBuf::from(FLOAT_STEREO_SINE), HOST_BUFFER_SIZE)
+        .ring_slices(ring_slices)
+        .build()
+        .unwrap();
+
+    // Priming only decodes the first ring
+    reader.read_sync().unwrap();
+    assert_eq!(reader.stats().samples, 2 * ring_slices * HOST_BUFFER_SIZE);
+    assert_eq!(reader.percentage_consumed(), 0.0);
+    assert_eq!(reader.remaining_samples(), ring_slices * HOST_BUFFER_SIZE);
+
+    let pregen_sine = sine_float_samples();
+    for chunk in pregen_sine.chunks(HOST_BUFFER_SIZE) {
+        let (left, right) = reader.next_slice().unwrap();
+        assert_eq!(left.len(), HOST_BUFFER_SIZE);
+        assert_eq!(&left[..chunk.len()], chunk);
+        assert_eq!(&right[..chunk.len()], chunk);
+        // The tail of the last slice is padded
+        assert_silence(&left[chunk.len()..]);
+        assert!(reader.percentage_consumed() <= 1.0);
+    }
+
+    // Past the end, the ring keeps issuing silence
+    for _ in 0..2 * ring_slices {
+        let (left, right) = reader.next_slice().unwrap();
+        assert_silence(left);
+        assert_silence(right);
+    }
+    assert_eq!(reader.stats().samples, 2 * SAMPLE_RATE as usize);
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "hound")]
+
+mod common;
+
+use audio_reader::{prelude::*, transcode_to_wav, Dither, WavFormat};
+use common::*;
+
+use std::path::Path;
+
+const TRANSCODED_MONO_SINE: &str = "assets/transcoded_mono_sine.wav";
+const TRANSCODED_STEREO_SINE: &str = "assets/transcoded_stereo_sine.wav";
+const WRITTEN_STEREO_SINE: &str = "assets/written_stereo_sine.wav";
+const WRITTEN_MONO_SINE: &str = "assets/written_mono_sine.wav";
+const DITHERED_MONO: &str = "assets/dithered_mono.wav";
+
+#[test]
+fn transcode_flac_to_int_wav() {
+    flac_mono_sine(FLAC_MONO_SINE, false);
+
+    let frames = transcode_to_wav(
+        Path::new(FLAC_MONO_SINE),
+        Path::new(TRANSCODED_MONO_SINE),
+        WavFormat::Int16,
+    )
+    .unwrap();
+    assert_eq!(frames, SAMPLE_RATE as u64);
+
+    let mut reader = hound::WavReader::open(TRANSCODED_MONO_SINE).unwrap();
+    assert_eq!(reader.spec(), MONO_INT);
+
+    let samples: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+    let expected = sine_int_samples();
+    assert_eq!(samples.len(), expected.len());
+    for (sample, expected) in samples.iter().zip(expected.iter()) {
+        // One step of rounding may be lost through the float conversion
+        assert!((*sample as i32 - *expected as i32).abs() <= 1);
+    }
+}
+
+#[test]
+fn transcode_float_wav_to_float_wav() {
+    stereo_float_sine();
+
+    let frames = transcode_to_wav(
+        Path::new(FLOAT_STEREO_SINE),
+        Path::new(TRANSCODED_STEREO_SINE),
+        WavFormat::Float32,
+    )
+    .unwrap();
+    assert_eq!(frames, SAMPLE_RATE as u64);
+
+    let mut reader = hound::WavReader::open(TRANSCODED_STEREO_SINE).unwrap();
+    assert_eq!(reader.spec(), STEREO_FLOAT);
+
+    let samples: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+    let (left, right): (Vec<f32>, Vec<f32>) =
+        samples.chunks(2).map(|frame| (frame[0], frame[1])).unzip();
+    let expected = sine_float_samples();
+    assert_eq!(left, expected);
+    assert_eq!(right, expected);
+}
+
+#[test]
+fn write_buffers_to_wav() {
+    let pregen_sine = sine_float_samples();
+    let mut stereo = StereoBuffer::new(pregen_sine.len());
+    stereo.append_slices(&pregen_sine, &pregen_sine);
+    // Clamped to full scale when written as integers
+    stereo.apply_gain(2.0);
+
+    stereo
+        .write_wav(Path::new(WRITTEN_STEREO_SINE), SAMPLE_RATE, 16)
+        .unwrap();
+    let mut reader = hound::WavReader::open(WRITTEN_STEREO_SINE).unwrap();
+    assert_eq!(reader.spec().channels, 2);
+    assert_eq!(reader.spec().sample_format, hound::SampleFormat::Int);
+
+    let samples: Vec<i16> = reader.samples::<i16>().map(Result::unwrap).collect();
+    assert_eq!(samples.len(), pregen_sine.len() * 2);
+    let expected = |sample: f32| ((sample * 2.0).clamp(-1.0, 1.0) * i16::MAX as f32).round() as i16;
+    for (frame, sample) in samples.chunks(2).zip(&pregen_sine) {
+        assert_eq!(frame, [expected(*sample); 2]);
+    }
+
+    let mut mono = MonoBuffer::new(pregen_sine.len());
+    mono.append_slice(&pregen_sine);
+    mono.write_wav(Path::new(WRITTEN_MONO_SINE), SAMPLE_RATE, 32)
+        .unwrap();
+    let mut reader = hound::WavReader::open(WRITTEN_MONO_SINE).unwrap();
+    assert_eq!(reader.spec().channels, 1);
+
+    let samples: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+    assert_eq!(samples, pregen_sine);
+
+    assert!(matches!(
+        mono.write_wav(Path::new(WRITTEN_MONO_SINE), SAMPLE_RATE, 8),
+        Err(hound::Error::Unsupported)
+    ));
+}
+
+#[test]
+fn dither_quiet_content() {
+    // A third of the least significant bit is rounded to silence without dither
+    let lsb = 1.0 / i16::MAX as f32;
+    let mut mono = MonoBuffer::new(SAMPLE_RATE as usize);
+    mono.append_slice(&vec![lsb / 3.0; SAMPLE_RATE as usize]);
+    let write = |dither: Dither, seed: u64| -> Vec<i16> {
+        mono.write_wav_dithered(Path::new(DITHERED_MONO), SAMPLE_RATE, 16, dither, seed)
+            .unwrap();
+        let mut reader = hound::WavReader::open(DITHERED_MONO).unwrap();
+        reader.samples::<i16>().map(Result::unwrap).collect()
+    };
+
+    assert!(write(Dither::None, 0).iter().all(|sample| *sample == 0));
+
+    for (dither, peak) in [(Dither::Rectangular, 1), (Dither::TriangularPdf, 2)] {
+        let samples = write(dither, 7);
+        assert!(samples.iter().all(|sample| sample.abs() <= peak));
+        // The content is preserved on average
+        let mean = samples.iter().map(|s| *s as f64).sum::<f64>() / samples.len() as f64;
+        assert!(
+            (mean - 1.0 / 3.0).abs() < 0.02,
+            "{:?} mean {}",
+            dither,
+            mean
+        );
+
+        assert_eq!(write(dither, 7), samples);
+        assert_ne!(write(dither, 8), samples);
+    }
+}